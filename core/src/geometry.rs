@@ -0,0 +1,249 @@
+use crate::keys::{thumb_cluster, Finger};
+use crate::words::TypingSide;
+use keyboard_types::Code;
+use serde::{Deserialize, Serialize};
+
+/// Position and size of a physical key, in units of a letter key
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct KeyShape {
+    pub code: Code,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+}
+
+/// Physical arrangement of the keys, independent of the layout printed on them
+#[derive(Clone, Debug, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Geometry {
+    /// Row-staggered board with a single row Enter
+    #[default]
+    Ansi,
+    /// Row-staggered board with a short left Shift and an extra key next to it
+    Iso,
+    /// Grid of same sized keys without any stagger
+    Ortholinear,
+    /// Two halves of six columns, each column staggered to the length of its finger,
+    /// with a cluster of keys under each thumb
+    Split,
+}
+
+/// Modifiers row of the row-staggered boards
+const MODIFIERS: [(Code, f32); 8] = [
+    (Code::ControlLeft, 1.25),
+    (Code::MetaLeft, 1.25),
+    (Code::AltLeft, 1.25),
+    (Code::Space, 6.25),
+    (Code::AltRight, 1.25),
+    (Code::MetaRight, 1.25),
+    (Code::ContextMenu, 1.25),
+    (Code::ControlRight, 1.25),
+];
+
+/// Vertical offset of each column of the left half of a split board, from the outer one
+const SPLIT_STAGGER: [f32; 6] = [0.5, 0.5, 0.25, 0.0, 0.25, 0.35];
+
+/// Space between the halves of a split board
+const SPLIT_GAP: f32 = 2.0;
+
+/// Lays `keys` left to right on row `y`
+fn row(keys: &[(Code, f32)], y: f32) -> Vec<KeyShape> {
+    let mut x = 0.0;
+
+    keys.iter()
+        .map(|&(code, width)| {
+            let shape = KeyShape { code, x, y, width };
+            x += width;
+            shape
+        })
+        .collect()
+}
+
+/// Lays `codes` as a row of letter sized keys
+fn units(codes: &[Code]) -> Vec<(Code, f32)> {
+    codes.iter().map(|&code| (code, 1.0)).collect()
+}
+
+/// Lays the columns of one half of a split board from `x`, with `stagger` from left to right
+fn split_half(rows: [&[Code]; 3], x: f32, stagger: &[f32; 6]) -> [Vec<KeyShape>; 3] {
+    let mut y = 0.0;
+
+    rows.map(|codes| {
+        let keys = codes
+            .iter()
+            .zip(stagger)
+            .enumerate()
+            .map(|(column, (&code, offset))| KeyShape {
+                code,
+                x: x + column as f32,
+                y: y + offset,
+                width: 1.0,
+            })
+            .collect();
+        y += 1.0;
+        keys
+    })
+}
+
+impl Geometry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Geometry::Ansi => "ansi",
+            Geometry::Iso => "iso",
+            Geometry::Ortholinear => "ortholinear",
+            Geometry::Split => "split",
+        }
+    }
+
+    /// Finger pressing `code` on this geometry while typing on `side`, thumbs taking over the keys
+    /// of split thumb clusters, and the thumb of `side` a Space shared by both
+    pub fn finger(&self, code: &Code, side: TypingSide) -> Option<Finger> {
+        match self {
+            Geometry::Split if thumb_cluster(TypingSide::Left).contains(code) => {
+                Some(Finger::LeftThumb)
+            }
+            Geometry::Split if thumb_cluster(TypingSide::Right).contains(code) => {
+                Some(Finger::RightThumb)
+            }
+            _ if *code == Code::Space => Some(Finger::thumb(side)),
+            _ => Finger::of(code),
+        }
+    }
+
+    /// Keys from the top letter row down to the modifiers, each row listed left to right
+    pub fn rows(&self) -> Vec<Vec<KeyShape>> {
+        use Code::*;
+
+        let top = [KeyQ, KeyW, KeyE, KeyR, KeyT, KeyY, KeyU, KeyI, KeyO, KeyP];
+        let home = [
+            KeyA, KeyS, KeyD, KeyF, KeyG, KeyH, KeyJ, KeyK, KeyL, Semicolon,
+        ];
+        let bottom = [
+            KeyZ, KeyX, KeyC, KeyV, KeyB, KeyN, KeyM, Comma, Period, Slash,
+        ];
+
+        match self {
+            Geometry::Ansi => vec![
+                row(
+                    &[
+                        &[(Tab, 1.5)],
+                        &units(&top)[..],
+                        &[(BracketLeft, 1.0), (BracketRight, 1.0), (Backslash, 1.5)],
+                    ]
+                    .concat(),
+                    0.0,
+                ),
+                row(
+                    &[
+                        &[(CapsLock, 1.75)],
+                        &units(&home)[..],
+                        &[(Quote, 1.0), (Enter, 2.25)],
+                    ]
+                    .concat(),
+                    1.0,
+                ),
+                row(
+                    &[
+                        &[(ShiftLeft, 2.25)],
+                        &units(&bottom)[..],
+                        &[(ShiftRight, 2.75)],
+                    ]
+                    .concat(),
+                    2.0,
+                ),
+                row(&MODIFIERS, 3.0),
+            ],
+            Geometry::Iso => vec![
+                row(
+                    &[
+                        &[(Tab, 1.5)],
+                        &units(&top)[..],
+                        &[(BracketLeft, 1.0), (BracketRight, 1.0)],
+                    ]
+                    .concat(),
+                    0.0,
+                ),
+                row(
+                    &[
+                        &[(CapsLock, 1.75)],
+                        &units(&home)[..],
+                        &[(Quote, 1.0), (Backslash, 1.0), (Enter, 1.25)],
+                    ]
+                    .concat(),
+                    1.0,
+                ),
+                row(
+                    &[
+                        &[(ShiftLeft, 1.25), (IntlBackslash, 1.0)],
+                        &units(&bottom)[..],
+                        &[(ShiftRight, 2.75)],
+                    ]
+                    .concat(),
+                    2.0,
+                ),
+                row(&MODIFIERS, 3.0),
+            ],
+            Geometry::Ortholinear => vec![
+                row(&units(&[&[Tab][..], &top, &[BracketLeft]].concat()), 0.0),
+                row(&units(&[&[CapsLock][..], &home, &[Quote]].concat()), 1.0),
+                row(&units(&[&[ShiftLeft][..], &bottom, &[Enter]].concat()), 2.0),
+                row(
+                    &[
+                        &units(&[ControlLeft, MetaLeft, AltLeft])[..],
+                        &[(Space, 5.0)],
+                        &units(&[AltRight, MetaRight, ContextMenu, ControlRight]),
+                    ]
+                    .concat(),
+                    3.0,
+                ),
+            ],
+            Geometry::Split => {
+                let mut mirrored = SPLIT_STAGGER;
+                mirrored.reverse();
+
+                let left = split_half(
+                    [
+                        &[&[Tab][..], &top[..5]].concat(),
+                        &[&[CapsLock][..], &home[..5]].concat(),
+                        &[&[ShiftLeft][..], &bottom[..5]].concat(),
+                    ],
+                    0.0,
+                    &SPLIT_STAGGER,
+                );
+                let right = split_half(
+                    [
+                        &[&top[5..], &[BracketLeft][..]].concat(),
+                        &[&home[5..], &[Quote][..]].concat(),
+                        &[&bottom[5..], &[ShiftRight][..]].concat(),
+                    ],
+                    6.0 + SPLIT_GAP,
+                    &mirrored,
+                );
+
+                // Thumbs reach below the inner columns of each half, from the inner key outwards
+                let left_thumbs = thumb_cluster(TypingSide::Left)
+                    .into_iter()
+                    .zip([5.0, 4.0, 3.0]);
+                let right_thumbs = thumb_cluster(TypingSide::Right)
+                    .into_iter()
+                    .zip([6.0, 7.0, 8.0].map(|column| column + SPLIT_GAP));
+                let mut thumbs: Vec<KeyShape> = left_thumbs
+                    .chain(right_thumbs)
+                    .map(|(code, x)| KeyShape {
+                        code,
+                        x,
+                        y: 3.6,
+                        width: 1.0,
+                    })
+                    .collect();
+                thumbs.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+                left.into_iter()
+                    .zip(right)
+                    .map(|(left, right)| [left, right].concat())
+                    .chain(std::iter::once(thumbs))
+                    .collect()
+            }
+        }
+    }
+}
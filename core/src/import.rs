@@ -0,0 +1,634 @@
+//! Importers of layouts defined for other tools: XKB `symbols` files and
+//! Microsoft Keyboard Layout Creator `.klc` files.
+//!
+//! Only the 30 letter keys are taken, split into halves by column the same way as the bundled layouts.
+//! Keys a file leaves undefined, such as those inherited through an XKB `include`, keep their qwerty legends.
+
+use crate::keys::legend_of;
+use crate::words::{LayoutDictionary, WordDictionary};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Legends of the letter rows of qwerty, from the top one down
+const QWERTY: [&str; 3] = ["qwertyuiop", "asdfghjkl;", "zxcvbnm,./"];
+
+/// Named XKB keysyms of the characters found on letter rows, in their lowercase forms.
+///
+/// Keysym names only differ by case between the cases of a letter, such as `Cyrillic_shorti` and
+/// `Cyrillic_SHORTI`, and legends are uppercased anyway, so names are compared ignoring case.
+const KEYSYMS: &[(&str, char)] = &[
+    // ASCII punctuation
+    ("space", ' '),
+    ("exclam", '!'),
+    ("quotedbl", '"'),
+    ("numbersign", '#'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("apostrophe", '\''),
+    ("parenleft", '('),
+    ("parenright", ')'),
+    ("asterisk", '*'),
+    ("plus", '+'),
+    ("comma", ','),
+    ("minus", '-'),
+    ("period", '.'),
+    ("slash", '/'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("less", '<'),
+    ("equal", '='),
+    ("greater", '>'),
+    ("question", '?'),
+    ("at", '@'),
+    ("bracketleft", '['),
+    ("backslash", '\\'),
+    ("bracketright", ']'),
+    ("asciicircum", '^'),
+    ("underscore", '_'),
+    ("grave", '`'),
+    ("braceleft", '{'),
+    ("bar", '|'),
+    ("braceright", '}'),
+    ("asciitilde", '~'),
+    // Latin-1
+    ("nobreakspace", '\u{a0}'),
+    ("exclamdown", '¡'),
+    ("cent", '¢'),
+    ("sterling", '£'),
+    ("currency", '¤'),
+    ("yen", '¥'),
+    ("brokenbar", '¦'),
+    ("section", '§'),
+    ("diaeresis", '¨'),
+    ("copyright", '©'),
+    ("ordfeminine", 'ª'),
+    ("guillemotleft", '«'),
+    ("notsign", '¬'),
+    ("hyphen", '\u{ad}'),
+    ("registered", '®'),
+    ("macron", '¯'),
+    ("degree", '°'),
+    ("plusminus", '±'),
+    ("twosuperior", '²'),
+    ("threesuperior", '³'),
+    ("acute", '´'),
+    ("mu", 'µ'),
+    ("paragraph", '¶'),
+    ("periodcentered", '·'),
+    ("cedilla", '¸'),
+    ("onesuperior", '¹'),
+    ("masculine", 'º'),
+    ("guillemotright", '»'),
+    ("onequarter", '¼'),
+    ("onehalf", '½'),
+    ("threequarters", '¾'),
+    ("questiondown", '¿'),
+    ("ssharp", 'ß'),
+    ("multiply", '×'),
+    ("agrave", 'à'),
+    ("aacute", 'á'),
+    ("acircumflex", 'â'),
+    ("atilde", 'ã'),
+    ("adiaeresis", 'ä'),
+    ("aring", 'å'),
+    ("ae", 'æ'),
+    ("ccedilla", 'ç'),
+    ("egrave", 'è'),
+    ("eacute", 'é'),
+    ("ecircumflex", 'ê'),
+    ("ediaeresis", 'ë'),
+    ("igrave", 'ì'),
+    ("iacute", 'í'),
+    ("icircumflex", 'î'),
+    ("idiaeresis", 'ï'),
+    ("eth", 'ð'),
+    ("ntilde", 'ñ'),
+    ("ograve", 'ò'),
+    ("oacute", 'ó'),
+    ("ocircumflex", 'ô'),
+    ("otilde", 'õ'),
+    ("odiaeresis", 'ö'),
+    ("division", '÷'),
+    ("oslash", 'ø'),
+    ("ugrave", 'ù'),
+    ("uacute", 'ú'),
+    ("ucircumflex", 'û'),
+    ("udiaeresis", 'ü'),
+    ("yacute", 'ý'),
+    ("thorn", 'þ'),
+    ("ydiaeresis", 'ÿ'),
+    ("guillemetleft", '«'),
+    ("guillemetright", '»'),
+    ("ooblique", 'ø'),
+    // Latin extended
+    ("aogonek", 'ą'),
+    ("abreve", 'ă'),
+    ("amacron", 'ā'),
+    ("cacute", 'ć'),
+    ("ccaron", 'č'),
+    ("dcaron", 'ď'),
+    ("dstroke", 'đ'),
+    ("eogonek", 'ę'),
+    ("ecaron", 'ě'),
+    ("emacron", 'ē'),
+    ("eabovedot", 'ė'),
+    ("gbreve", 'ğ'),
+    ("gcedilla", 'ģ'),
+    ("idotless", 'ı'),
+    ("imacron", 'ī'),
+    ("iogonek", 'į'),
+    ("kcedilla", 'ķ'),
+    ("lacute", 'ĺ'),
+    ("lcaron", 'ľ'),
+    ("lcedilla", 'ļ'),
+    ("lstroke", 'ł'),
+    ("nacute", 'ń'),
+    ("ncaron", 'ň'),
+    ("ncedilla", 'ņ'),
+    ("odoubleacute", 'ő'),
+    ("oe", 'œ'),
+    ("racute", 'ŕ'),
+    ("rcaron", 'ř'),
+    ("sacute", 'ś'),
+    ("scaron", 'š'),
+    ("scedilla", 'ş'),
+    ("tcaron", 'ť'),
+    ("tcedilla", 'ţ'),
+    ("udoubleacute", 'ű'),
+    ("umacron", 'ū'),
+    ("uogonek", 'ų'),
+    ("uring", 'ů'),
+    ("zacute", 'ź'),
+    ("zcaron", 'ž'),
+    ("zabovedot", 'ż'),
+    // Cyrillic
+    ("Cyrillic_a", 'а'),
+    ("Cyrillic_be", 'б'),
+    ("Cyrillic_ve", 'в'),
+    ("Cyrillic_ghe", 'г'),
+    ("Cyrillic_de", 'д'),
+    ("Cyrillic_ie", 'е'),
+    ("Cyrillic_io", 'ё'),
+    ("Cyrillic_zhe", 'ж'),
+    ("Cyrillic_ze", 'з'),
+    ("Cyrillic_i", 'и'),
+    ("Cyrillic_shorti", 'й'),
+    ("Cyrillic_ka", 'к'),
+    ("Cyrillic_el", 'л'),
+    ("Cyrillic_em", 'м'),
+    ("Cyrillic_en", 'н'),
+    ("Cyrillic_o", 'о'),
+    ("Cyrillic_pe", 'п'),
+    ("Cyrillic_er", 'р'),
+    ("Cyrillic_es", 'с'),
+    ("Cyrillic_te", 'т'),
+    ("Cyrillic_u", 'у'),
+    ("Cyrillic_ef", 'ф'),
+    ("Cyrillic_ha", 'х'),
+    ("Cyrillic_tse", 'ц'),
+    ("Cyrillic_che", 'ч'),
+    ("Cyrillic_sha", 'ш'),
+    ("Cyrillic_shcha", 'щ'),
+    ("Cyrillic_hardsign", 'ъ'),
+    ("Cyrillic_yeru", 'ы'),
+    ("Cyrillic_softsign", 'ь'),
+    ("Cyrillic_e", 'э'),
+    ("Cyrillic_yu", 'ю'),
+    ("Cyrillic_ya", 'я'),
+    ("Cyrillic_je", 'ј'),
+    ("Cyrillic_lje", 'љ'),
+    ("Cyrillic_nje", 'њ'),
+    ("Cyrillic_dzhe", 'џ'),
+    ("Ukrainian_ie", 'є'),
+    ("Ukrainian_i", 'і'),
+    ("Ukrainian_yi", 'ї'),
+    ("Ukrainian_ghe_with_upturn", 'ґ'),
+    ("Byelorussian_shortu", 'ў'),
+    ("Serbian_dje", 'ђ'),
+    ("Serbian_tshe", 'ћ'),
+    ("Macedonia_gje", 'ѓ'),
+    ("Macedonia_dse", 'ѕ'),
+    ("Macedonia_kje", 'ќ'),
+    // Greek
+    ("Greek_alpha", 'α'),
+    ("Greek_beta", 'β'),
+    ("Greek_gamma", 'γ'),
+    ("Greek_delta", 'δ'),
+    ("Greek_epsilon", 'ε'),
+    ("Greek_zeta", 'ζ'),
+    ("Greek_eta", 'η'),
+    ("Greek_theta", 'θ'),
+    ("Greek_iota", 'ι'),
+    ("Greek_kappa", 'κ'),
+    ("Greek_lamda", 'λ'),
+    ("Greek_lambda", 'λ'),
+    ("Greek_mu", 'μ'),
+    ("Greek_nu", 'ν'),
+    ("Greek_xi", 'ξ'),
+    ("Greek_omicron", 'ο'),
+    ("Greek_pi", 'π'),
+    ("Greek_rho", 'ρ'),
+    ("Greek_sigma", 'σ'),
+    ("Greek_finalsmallsigma", 'ς'),
+    ("Greek_tau", 'τ'),
+    ("Greek_upsilon", 'υ'),
+    ("Greek_phi", 'φ'),
+    ("Greek_chi", 'χ'),
+    ("Greek_psi", 'ψ'),
+    ("Greek_omega", 'ω'),
+    ("Greek_alphaaccent", 'ά'),
+    ("Greek_epsilonaccent", 'έ'),
+    ("Greek_etaaccent", 'ή'),
+    ("Greek_iotaaccent", 'ί'),
+    ("Greek_omicronaccent", 'ό'),
+    ("Greek_upsilonaccent", 'ύ'),
+    ("Greek_omegaaccent", 'ώ'),
+    ("Greek_iotadieresis", 'ϊ'),
+    ("Greek_upsilondieresis", 'ϋ'),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportError {
+    /// Neither an XKB symbols file nor a KLC file
+    UnknownFormat,
+    /// KLC character that is neither a single character nor a code point
+    UnknownSymbol(String),
+    /// The file defines none of the letter keys
+    NoKeys,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::UnknownFormat => write!(f, "not an XKB symbols or KLC file"),
+            ImportError::UnknownSymbol(symbol) => write!(f, "unknown symbol {symbol}"),
+            ImportError::NoKeys => write!(f, "no letter keys defined"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Key rows of both halves of an imported layout, in the format of [`WordDictionary::keys`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutKeys {
+    pub left: String,
+    pub right: String,
+}
+
+impl LayoutKeys {
+    /// Splits rows of 10 legends into halves of 5 columns
+    fn from_rows(rows: [[Option<char>; 10]; 3]) -> Result<Self, ImportError> {
+        if rows.iter().flatten().all(Option::is_none) {
+            return Err(ImportError::NoKeys);
+        }
+
+        let legends = rows.iter().zip(QWERTY).map(|(row, qwerty)| {
+            row.iter()
+                .zip(qwerty.chars())
+                .map(|(legend, fallback)| legend_of(legend.unwrap_or(fallback)))
+                .collect::<Vec<char>>()
+        });
+
+        let (left, right): (Vec<String>, Vec<String>) = legends
+            .map(|row| {
+                let (left, right) = row.split_at(5);
+                (left.iter().collect(), right.iter().collect())
+            })
+            .unzip();
+
+        Ok(LayoutKeys {
+            left: left.join(" "),
+            right: right.join(" "),
+        })
+    }
+
+    /// Builds a dictionary of the layout from `words`, giving each half the ones typed entirely on it
+    pub fn dictionary<'a>(&self, words: impl IntoIterator<Item = &'a String>) -> LayoutDictionary {
+        let words: HashSet<&String> = words.into_iter().collect();
+
+        let half = |keys: &str| {
+            let legends: HashSet<char> = keys.chars().flat_map(char::to_lowercase).collect();
+            let mut typed: Vec<String> = words
+                .iter()
+                .filter(|word| {
+                    word.chars()
+                        .flat_map(char::to_lowercase)
+                        .all(|c| legends.contains(&c))
+                })
+                .map(|word| word.to_string())
+                .collect();
+            typed.sort();
+
+            if typed.is_empty() {
+                typed.push("<space>".to_owned());
+            }
+
+            WordDictionary::new(typed, keys.to_owned())
+        };
+
+        LayoutDictionary {
+            left: half(&self.left),
+            right: half(&self.right),
+        }
+    }
+}
+
+/// Decodes the text of a layout file, KLC files being usually saved as UTF-16
+pub fn decode(bytes: &[u8]) -> String {
+    let utf16 = |to_u16: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| to_u16([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+
+    match bytes {
+        [0xFF, 0xFE, ..] => utf16(u16::from_le_bytes),
+        [0xFE, 0xFF, ..] => utf16(u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Parses `text` as whichever of the supported formats it looks like
+pub fn import(text: &str) -> Result<LayoutKeys, ImportError> {
+    if text.contains("xkb_symbols") || text.contains("key <") {
+        parse_xkb(text)
+    } else if text.lines().any(|line| line.starts_with("LAYOUT")) {
+        parse_klc(text)
+    } else {
+        Err(ImportError::UnknownFormat)
+    }
+}
+
+/// Parses the first `xkb_symbols` block of an XKB symbols file, taking the first level of each key.
+///
+/// Keys with keysyms Hemi doesn't know, such as `dead_acute`, keep their qwerty legends.
+pub fn parse_xkb(text: &str) -> Result<LayoutKeys, ImportError> {
+    let text: String = text
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let block = text.split("xkb_symbols").nth(1).unwrap_or(&text);
+    let mut rows = [[None; 10]; 3];
+
+    for definition in block.split("key <").skip(1) {
+        let Some((name, levels)) = definition.split_once('>') else {
+            continue;
+        };
+        let Some(position) = xkb_position(name) else {
+            continue;
+        };
+
+        // Levels are the last bracketed list, after an optional `symbols[Group1] =`
+        let levels = levels.split_once('}').map_or(levels, |(levels, _)| levels);
+        let Some((_, levels)) = levels.rsplit_once('[') else {
+            continue;
+        };
+        let Some(symbol) = levels.split([',', ']']).next().map(str::trim) else {
+            continue;
+        };
+
+        let (row, column) = position;
+        rows[row][column] = xkb_keysym(symbol);
+    }
+
+    LayoutKeys::from_rows(rows)
+}
+
+/// Row and column of the XKB key `name`, such as `AD01` for the top left letter key
+fn xkb_position(name: &str) -> Option<(usize, usize)> {
+    let row = match name.get(..2)? {
+        "AD" => 0,
+        "AC" => 1,
+        "AB" => 2,
+        _ => return None,
+    };
+    let column = name.get(2..)?.parse::<usize>().ok()?;
+
+    (1..=10).contains(&column).then_some((row, column - 1))
+}
+
+/// Character of the keysym `symbol`, written as itself, as `U` and a code point, or by its name
+fn xkb_keysym(symbol: &str) -> Option<char> {
+    let mut chars = symbol.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Some(char);
+    }
+
+    if let Some(code) = symbol.strip_prefix('U') {
+        if let Some(char) = u32::from_str_radix(code, 16).ok().and_then(char::from_u32) {
+            return Some(char);
+        }
+    }
+
+    KEYSYMS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(symbol))
+        .map(|&(_, char)| char)
+}
+
+/// Parses the `LAYOUT` section of a KLC file, taking the unshifted character of each key
+pub fn parse_klc(text: &str) -> Result<LayoutKeys, ImportError> {
+    let mut rows = [[None; 10]; 3];
+
+    let section = text
+        .lines()
+        .skip_while(|line| !line.starts_with("LAYOUT"))
+        .skip(1);
+
+    for line in section {
+        let line = line.split("//").next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let Some(scancode) = fields.next() else {
+            continue;
+        };
+
+        // Any other keyword starts the next section
+        let Ok(scancode) = u8::from_str_radix(scancode, 16) else {
+            break;
+        };
+        let Some(position) = klc_position(scancode) else {
+            continue;
+        };
+
+        // Virtual key and caps lock behaviour come before the characters of each shift state
+        let Some(character) = fields.nth(2) else {
+            continue;
+        };
+        let (row, column) = position;
+        rows[row][column] = klc_character(character)?;
+    }
+
+    LayoutKeys::from_rows(rows)
+}
+
+/// Row and column of the key with `scancode`, such as `10` for the top left letter key
+fn klc_position(scancode: u8) -> Option<(usize, usize)> {
+    match scancode {
+        0x10..=0x19 => Some((0, (scancode - 0x10) as usize)),
+        0x1e..=0x27 => Some((1, (scancode - 0x1e) as usize)),
+        0x2c..=0x35 => Some((2, (scancode - 0x2c) as usize)),
+        _ => None,
+    }
+}
+
+/// Character written as itself or as 4 hex digits, with `@` marking dead keys and `-1` no character
+fn klc_character(character: &str) -> Result<Option<char>, ImportError> {
+    let character = character.trim_end_matches('@');
+    if character == "-1" || character == "%%" {
+        return Ok(None);
+    }
+
+    let mut chars = character.chars();
+    if let (Some(char), None) = (chars.next(), chars.next()) {
+        return Ok(Some(char));
+    }
+
+    u32::from_str_radix(character, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .map(Some)
+        .ok_or_else(|| ImportError::UnknownSymbol(character.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XKB_US: &str = r#"
+default partial alphanumeric_keys modifier_keys
+xkb_symbols "basic" {
+    name[Group1]= "English (US)";
+
+    key <AD01> {	[	  q,	Q	]	};
+    key <AD02> {	[	  w,	W	]	};
+    key <AD10> {	[	  p,	P	]	};
+    key <AC10> {	[ semicolon,	colon	]	};
+    key <AB08> {	[     comma,	less	]	};
+    key <AB09> {	[    period,	greater	]	};
+    key <AB10> {	[     slash,	question	]	};
+};
+"#;
+
+    const XKB_RU: &str = r#"
+default partial alphanumeric_keys
+xkb_symbols "common" {
+    key <AD01> {	[	Cyrillic_shorti,	Cyrillic_SHORTI	]	};
+    key <AD02> {	[	   Cyrillic_tse,	   Cyrillic_TSE	]	};
+    key <AD06> {	[	    Cyrillic_en,	    Cyrillic_EN	]	};
+    key <AC01> {	[	    Cyrillic_ef,	    Cyrillic_EF	]	};
+    key <AC10> {	[	   Cyrillic_zhe,	   Cyrillic_ZHE	]	};
+    key <AB10> {	[	 period,	comma	]	};
+};
+"#;
+
+    const XKB_DE: &str = r#"
+default partial alphanumeric_keys
+xkb_symbols "basic" {
+    include "latin(type4)"
+    name[Group1]="German";
+
+    key <AD06>	{ [         z,          Z,    leftarrow,          yen ] };
+    key <AC10>	{ [odiaeresis, Odiaeresis, dead_doubleacute, dead_doubleacute ] };
+    key <AB01>	{ [         y,          Y,      guillemotright,    U203A ] };
+    key <AB10>	{ [     minus, underscore,       endash,       emdash ] };
+    key <AD11>	{ [udiaeresis, Udiaeresis, dead_diaeresis, dead_abovering ] };
+};
+"#;
+
+    #[test]
+    fn parses_xkb_us() {
+        let keys = import(XKB_US).unwrap();
+        assert_eq!(keys.left, "QWERT ASDFG ZXCVB");
+        assert_eq!(keys.right, "YUIOP HJKL; NM,./");
+    }
+
+    #[test]
+    fn parses_xkb_cyrillic_keysyms() {
+        let keys = import(XKB_RU).unwrap();
+        assert_eq!(keys.left, "ЙЦERT ФSDFG ZXCVB");
+        assert_eq!(keys.right, "НUIOP HJKLЖ NM,..");
+    }
+
+    #[test]
+    fn parses_xkb_latin_keysyms() {
+        let keys = import(XKB_DE).unwrap();
+        assert_eq!(keys.left, "QWERT ASDFG YXCVB");
+        assert_eq!(keys.right, "ZUIOP HJKLÖ NM,.-");
+    }
+
+    #[test]
+    fn parses_xkb_explicit_group() {
+        let text = r#"xkb_symbols "basic" {
+            key <AD01> { type = "ALPHABETIC", symbols[Group1] = [ a, A ] };
+            key <AD02> { symbols[Group1]= [ U0444, U0424 ] };
+        };"#;
+        let keys = parse_xkb(text).unwrap();
+        assert_eq!(keys.left, "AФERT ASDFG ZXCVB");
+    }
+
+    #[test]
+    fn ignores_xkb_comments() {
+        let text = r#"xkb_symbols "basic" {
+            key <AD01> { [ a, A ] };
+            // key <AD05> { [ g, G ] };
+            key <AD02> { [ b, B ] }; // key <AD03> { [ c, C ] };
+        };"#;
+        let keys = parse_xkb(text).unwrap();
+        assert_eq!(keys.left, "ABERT ASDFG ZXCVB");
+    }
+
+    #[test]
+    fn keeps_qwerty_for_unknown_keysyms() {
+        let text = r#"xkb_symbols "basic" {
+            key <AD01> { [ dead_acute, dead_grave ] };
+            key <AD02> { [ eacute, Eacute ] };
+        };"#;
+        let keys = parse_xkb(text).unwrap();
+        assert_eq!(keys.left, "QÉERT ASDFG ZXCVB");
+    }
+
+    #[test]
+    fn parses_klc() {
+        let text = "KBD\tTest\t\"Test\"\r\n\
+            \r\n\
+            LAYOUT\t\t;an extra '@' at the end is a dead key\r\n\
+            \r\n\
+            //SC\tVK_\t\tCap\t0\t1\t2\r\n\
+            //--\t----\t\t----\t----\t----\t----\r\n\
+            \r\n\
+            02\t1\t\t0\t1\t0021\t-1\t\t// DIGIT ONE\r\n\
+            10\tQ\t\t1\tq\tQ\t-1\t\t// LATIN SMALL LETTER Q\r\n\
+            11\tW\t\t1\t0446\t0426\t-1\t\t// CYRILLIC SMALL LETTER TSE\r\n\
+            27\tOEM_1\t\t1\t00f6\t00d6\t-1\t\t// LATIN SMALL LETTER O WITH DIAERESIS\r\n\
+            2c\tY\t\t1\ty\tY\t-1\r\n\
+            35\tOEM_MINUS\t0\t-\t_\t-1\r\n\
+            \r\n\
+            DEADKEY\t005e\r\n\
+            \r\n\
+            0061\t00e2\r\n";
+
+        let keys = import(text).unwrap();
+        assert_eq!(keys.left, "QЦERT ASDFG YXCVB");
+        assert_eq!(keys.right, "YUIOP HJKLÖ NM,.-");
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(import(&decode(&utf16)).unwrap(), keys);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(import("hello"), Err(ImportError::UnknownFormat));
+        assert_eq!(
+            import("xkb_symbols \"x\" { key <ESC> { [ Escape ] }; };"),
+            Err(ImportError::NoKeys)
+        );
+    }
+}
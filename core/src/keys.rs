@@ -0,0 +1,484 @@
+use crate::geometry::{Geometry, KeyShape};
+use crate::words::{LayoutDictionary, TypingSide};
+use keyboard_types::Code;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Physical keys of the left half, from the number row down to the modifiers
+pub(crate) const LEFT_CODES: [&[Code]; 5] = [
+    &[
+        Code::Backquote,
+        Code::Digit1,
+        Code::Digit2,
+        Code::Digit3,
+        Code::Digit4,
+        Code::Digit5,
+    ],
+    &[
+        Code::Tab,
+        Code::KeyQ,
+        Code::KeyW,
+        Code::KeyE,
+        Code::KeyR,
+        Code::KeyT,
+    ],
+    &[
+        Code::CapsLock,
+        Code::KeyA,
+        Code::KeyS,
+        Code::KeyD,
+        Code::KeyF,
+        Code::KeyG,
+    ],
+    &[
+        Code::ShiftLeft,
+        Code::IntlBackslash,
+        Code::KeyZ,
+        Code::KeyX,
+        Code::KeyC,
+        Code::KeyV,
+        Code::KeyB,
+    ],
+    &[Code::ControlLeft, Code::MetaLeft, Code::AltLeft],
+];
+
+/// Physical keys of the right half, from the number row down to the modifiers
+pub(crate) const RIGHT_CODES: [&[Code]; 5] = [
+    &[
+        Code::Digit6,
+        Code::Digit7,
+        Code::Digit8,
+        Code::Digit9,
+        Code::Digit0,
+        Code::Minus,
+        Code::Equal,
+    ],
+    &[
+        Code::KeyY,
+        Code::KeyU,
+        Code::KeyI,
+        Code::KeyO,
+        Code::KeyP,
+        Code::BracketLeft,
+        Code::BracketRight,
+        Code::Backslash,
+    ],
+    &[
+        Code::KeyH,
+        Code::KeyJ,
+        Code::KeyK,
+        Code::KeyL,
+        Code::Semicolon,
+        Code::Quote,
+        Code::Enter,
+    ],
+    &[
+        Code::KeyN,
+        Code::KeyM,
+        Code::Comma,
+        Code::Period,
+        Code::Slash,
+        Code::ShiftRight,
+    ],
+    &[
+        Code::AltRight,
+        Code::MetaRight,
+        Code::ContextMenu,
+        Code::ControlRight,
+    ],
+];
+
+/// Physical keys the rows of the left half of [`WordDictionary::keys`] are located on
+///
+/// [`WordDictionary::keys`]: crate::words::WordDictionary::keys
+const LEFT_KEYS: [&[Code]; 3] = [
+    &[Code::KeyQ, Code::KeyW, Code::KeyE, Code::KeyR, Code::KeyT],
+    &[Code::KeyA, Code::KeyS, Code::KeyD, Code::KeyF, Code::KeyG],
+    &[Code::KeyZ, Code::KeyX, Code::KeyC, Code::KeyV, Code::KeyB],
+];
+
+/// Physical keys the rows of the right half of [`WordDictionary::keys`] are located on
+///
+/// [`WordDictionary::keys`]: crate::words::WordDictionary::keys
+const RIGHT_KEYS: [&[Code]; 3] = [
+    &[Code::KeyY, Code::KeyU, Code::KeyI, Code::KeyO, Code::KeyP],
+    &[
+        Code::KeyH,
+        Code::KeyJ,
+        Code::KeyK,
+        Code::KeyL,
+        Code::Semicolon,
+    ],
+    &[
+        Code::KeyN,
+        Code::KeyM,
+        Code::Comma,
+        Code::Period,
+        Code::Slash,
+    ],
+];
+
+fn half_keys(side: TypingSide) -> &'static [&'static [Code]; 3] {
+    match side {
+        TypingSide::Left => &LEFT_KEYS,
+        TypingSide::Right => &RIGHT_KEYS,
+    }
+}
+
+/// Legend of a physical key outside the rows of [`WordDictionary::keys`]
+///
+/// [`WordDictionary::keys`]: crate::words::WordDictionary::keys
+fn fixed_legend(code: &Code) -> Option<&'static str> {
+    Some(match code {
+        Code::Tab => "tab",
+        Code::CapsLock => "caps",
+        Code::ShiftLeft | Code::ShiftRight => "shift",
+        Code::ControlLeft | Code::ControlRight => "ctrl",
+        Code::MetaLeft | Code::MetaRight => "meta",
+        Code::AltLeft | Code::AltRight => "alt",
+        Code::ContextMenu => "menu",
+        Code::Space => "space",
+        Code::Enter => "enter",
+        Code::BracketLeft => "[",
+        Code::BracketRight => "]",
+        Code::Backslash | Code::IntlBackslash => "\\",
+        Code::Quote => "'",
+        _ => return None,
+    })
+}
+
+/// Only character of `chars`, or `fallback` if there are several, like the uppercase `SS` of `ß`
+fn single(mut chars: impl Iterator<Item = char>, fallback: char) -> char {
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => char,
+        _ => fallback,
+    }
+}
+
+/// Legend printed on the key typing `char`, its uppercase form in any script
+pub(crate) fn legend_of(char: char) -> char {
+    single(char.to_uppercase(), char)
+}
+
+/// Whether `a` and `b` are the same character, ignoring case
+fn same_letter(a: char, b: char) -> bool {
+    a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Finger pressing a key in touch typing
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    /// Finger owning the physical key `code`, the same for every layout laid over it.
+    ///
+    /// Space is shared by both thumbs, see [`Geometry::finger`] for the one pressing it
+    pub fn of(code: &Code) -> Option<Self> {
+        use Code::*;
+
+        Some(match code {
+            Backquote | Digit1 | Tab | KeyQ | CapsLock | KeyA | ShiftLeft | IntlBackslash
+            | KeyZ | ControlLeft => Finger::LeftPinky,
+            Digit2 | KeyW | KeyS | KeyX => Finger::LeftRing,
+            Digit3 | KeyE | KeyD | KeyC => Finger::LeftMiddle,
+            Digit4 | Digit5 | KeyR | KeyT | KeyF | KeyG | KeyV | KeyB => Finger::LeftIndex,
+            MetaLeft | AltLeft => Finger::LeftThumb,
+            AltRight | MetaRight | ContextMenu => Finger::RightThumb,
+            Digit6 | Digit7 | KeyY | KeyU | KeyH | KeyJ | KeyN | KeyM => Finger::RightIndex,
+            Digit8 | KeyI | KeyK | Comma => Finger::RightMiddle,
+            Digit9 | KeyO | KeyL | Period => Finger::RightRing,
+            Digit0 | Minus | Equal | KeyP | BracketLeft | BracketRight | Backslash | Semicolon
+            | Quote | Slash | ShiftRight | ControlRight | Enter | Backspace => Finger::RightPinky,
+            _ => return None,
+        })
+    }
+
+    /// Thumb of the hand typing on `side`
+    pub fn thumb(side: TypingSide) -> Self {
+        match side {
+            TypingSide::Left => Finger::LeftThumb,
+            TypingSide::Right => Finger::RightThumb,
+        }
+    }
+
+    /// Half of the keyboard the hand of this finger types on
+    pub fn side(&self) -> TypingSide {
+        match self {
+            Finger::LeftPinky
+            | Finger::LeftRing
+            | Finger::LeftMiddle
+            | Finger::LeftIndex
+            | Finger::LeftThumb => TypingSide::Left,
+            Finger::RightThumb
+            | Finger::RightIndex
+            | Finger::RightMiddle
+            | Finger::RightRing
+            | Finger::RightPinky => TypingSide::Right,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Finger::LeftPinky => "left pinky",
+            Finger::LeftRing => "left ring",
+            Finger::LeftMiddle => "left middle",
+            Finger::LeftIndex => "left index",
+            Finger::LeftThumb => "left thumb",
+            Finger::RightThumb => "right thumb",
+            Finger::RightIndex => "right index",
+            Finger::RightMiddle => "right middle",
+            Finger::RightRing => "right ring",
+            Finger::RightPinky => "right pinky",
+        }
+    }
+}
+
+/// Key of a thumb cluster, pressed by the thumb of either half
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbKey {
+    Space,
+    Enter,
+    Alt,
+    Meta,
+}
+
+impl ThumbKey {
+    /// Physical key reported when the thumb of `side` presses this key
+    pub fn code(&self, side: TypingSide) -> Code {
+        match (self, side) {
+            (ThumbKey::Space, _) => Code::Space,
+            (ThumbKey::Enter, _) => Code::Enter,
+            (ThumbKey::Alt, TypingSide::Left) => Code::AltLeft,
+            (ThumbKey::Alt, TypingSide::Right) => Code::AltRight,
+            (ThumbKey::Meta, TypingSide::Left) => Code::MetaLeft,
+            (ThumbKey::Meta, TypingSide::Right) => Code::MetaRight,
+        }
+    }
+}
+
+/// Thumb cluster of the half `side` on a split keyboard, from the inner key outwards
+pub fn thumb_cluster(side: TypingSide) -> [Code; 3] {
+    match side {
+        TypingSide::Left => [Code::Space, Code::AltLeft, Code::MetaLeft],
+        TypingSide::Right => [Code::Enter, Code::AltRight, Code::MetaRight],
+    }
+}
+
+/// Maps physical key [`Code`]s to legends of a layout
+#[derive(Clone, Default)]
+pub struct KeyMap {
+    legends: HashMap<Code, char>,
+}
+
+impl KeyMap {
+    /// Lays the key rows of both halves of `dictionary` over the physical keys
+    pub fn new(dictionary: &LayoutDictionary) -> Self {
+        let mut legends = HashMap::new();
+
+        for side in [TypingSide::Left, TypingSide::Right] {
+            let rows = dictionary.side(side).keys().split_whitespace();
+            for (codes, row) in half_keys(side).iter().zip(rows) {
+                legends.extend(codes.iter().copied().zip(row.chars().map(legend_of)));
+            }
+        }
+
+        KeyMap { legends }
+    }
+
+    pub fn legend(&self, code: &Code) -> Option<char> {
+        self.legends.get(code).copied()
+    }
+
+    /// Finds the physical key with `legend`, for front ends that only receive characters
+    pub fn code_of(&self, legend: char) -> Option<Code> {
+        self.legends
+            .iter()
+            .find(|(_, &other)| same_letter(other, legend))
+            .map(|(&code, _)| code)
+    }
+
+    /// Returns the character the physical key `code` produces in this layout,
+    /// regardless of the layout selected in the OS
+    pub fn emulate(&self, code: &Code, shift: bool) -> Option<char> {
+        let legend = self.legend(code)?;
+
+        if !shift {
+            return Some(single(legend.to_lowercase(), legend));
+        }
+
+        Some(match legend {
+            ';' => ':',
+            ',' => '<',
+            '.' => '>',
+            '/' => '?',
+            '\'' => '"',
+            legend => legend_of(legend),
+        })
+    }
+}
+
+/// Stores pressed state of a physical key
+#[derive(PartialEq, Clone)]
+pub struct KeyState {
+    shape: KeyShape,
+    finger: Option<Finger>,
+    legend: String,
+    enabled: bool,
+    /// Whether the key belongs to the half being trained
+    active: bool,
+}
+
+impl KeyState {
+    pub fn code(&self) -> Code {
+        self.shape.code
+    }
+
+    pub fn shape(&self) -> &KeyShape {
+        &self.shape
+    }
+
+    pub fn finger(&self) -> Option<Finger> {
+        self.finger
+    }
+
+    pub fn legend(&self) -> &str {
+        &self.legend
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Stores rows of [`KeyState`]s for the whole keyboard, from the top letter row down to the modifiers,
+/// positioned by a [`Geometry`]
+#[derive(Clone)]
+pub struct KeyboardState {
+    keys: Vec<Vec<KeyState>>,
+    positions: HashMap<Code, (usize, usize)>,
+}
+
+impl KeyboardState {
+    /// Lays out both halves of `map` on `geometry`, marking the keys of `side` as active
+    pub fn new(map: &KeyMap, side: TypingSide, geometry: Geometry) -> Self {
+        let keys: Vec<Vec<KeyState>> = geometry
+            .rows()
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .filter_map(|shape| {
+                        let code = shape.code;
+                        let legend = map
+                            .legend(&code)
+                            .map(String::from)
+                            .or_else(|| fixed_legend(&code).map(String::from))?;
+                        let finger = geometry.finger(&code, side);
+
+                        Some(KeyState {
+                            shape,
+                            finger,
+                            legend,
+                            enabled: false,
+                            // Thumb keys belong to the half of the thumb pressing them
+                            active: TypingSide::of_code(&code)
+                                .or(finger.map(|finger| finger.side()))
+                                .is_none_or(|half| half == side),
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let positions = keys
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(j, key)| (key.code(), (i, j)))
+            })
+            .collect();
+
+        KeyboardState { keys, positions }
+    }
+
+    pub fn get(&self, code: &Code) -> Option<&KeyState> {
+        let &(row, column) = self.positions.get(code)?;
+        Some(&self.keys[row][column])
+    }
+
+    pub fn update_for(&mut self, code: &Code, enabled: bool) {
+        if let Some(&(row, column)) = self.positions.get(code) {
+            self.keys[row][column].enabled = enabled;
+        }
+    }
+
+    pub fn keys(&self) -> &Vec<Vec<KeyState>> {
+        self.keys.as_ref()
+    }
+
+    /// Width and height taken by all the keys, in units of a letter key
+    pub fn size(&self) -> (f32, f32) {
+        self.keys
+            .iter()
+            .flatten()
+            .fold((0.0, 0.0), |(width, height), key| {
+                let shape = key.shape();
+                (width.max(shape.x + shape.width), height.max(shape.y + 1.0))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_is_pressed_by_the_active_thumb() {
+        let map = KeyMap::new(&LayoutDictionary::default());
+
+        for side in [TypingSide::Left, TypingSide::Right] {
+            let keyboard = KeyboardState::new(&map, side, Geometry::Ansi);
+            let space = keyboard.get(&Code::Space).unwrap();
+            assert_eq!(space.finger(), Some(Finger::thumb(side)));
+        }
+
+        // Split boards put Space under the left thumb only
+        let keyboard = KeyboardState::new(&map, TypingSide::Right, Geometry::Split);
+        let space = keyboard.get(&Code::Space).unwrap();
+        assert_eq!(space.finger(), Some(Finger::LeftThumb));
+    }
+
+    #[test]
+    fn thumb_clusters_are_active_for_their_half() {
+        let map = KeyMap::new(&LayoutDictionary::default());
+        let keyboard = KeyboardState::new(&map, TypingSide::Right, Geometry::Split);
+
+        for code in thumb_cluster(TypingSide::Left) {
+            assert!(!keyboard.get(&code).unwrap().active(), "{code} is active");
+        }
+        for code in thumb_cluster(TypingSide::Right) {
+            assert!(keyboard.get(&code).unwrap().active(), "{code} is inactive");
+        }
+
+        // Off split boards Space follows the active side
+        let keyboard = KeyboardState::new(&map, TypingSide::Right, Geometry::Ansi);
+        assert!(keyboard.get(&Code::Space).unwrap().active());
+    }
+}
@@ -0,0 +1,7 @@
+//! Typing engine shared by the Hemi front ends and the race server
+
+pub mod geometry;
+pub mod import;
+pub mod keys;
+pub mod protocol;
+pub mod words;
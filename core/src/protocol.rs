@@ -0,0 +1,92 @@
+//! Messages of the race protocol, exchanged as JSON text frames over a WebSocket.
+//!
+//! A race goes through the following steps:
+//! 1. Clients send [`ClientMessage::Join`] with a room name, the first one to join
+//!    picks layout and side of the room.
+//! 2. Any player sends [`ClientMessage::Start`], the server picks a seed and broadcasts
+//!    [`ServerMessage::Start`] with the words everyone types.
+//! 3. Clients send [`ClientMessage::Progress`] after every submitted word,
+//!    which the server relays to the room as [`ServerMessage::Progress`].
+//! 4. Clients send [`ClientMessage::Finish`] once all words are submitted,
+//!    the server broadcasts [`ServerMessage::Finished`] with their place.
+
+use crate::words::{LayoutDictionary, TypingSide};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Path the race server accepts WebSocket connections on
+pub const RACE_PATH: &str = "/api/race";
+
+/// Amount of words in a race
+pub const RACE_WORDS: usize = 25;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join {
+        room: String,
+        name: String,
+        layout: String,
+        side: TypingSide,
+    },
+    Start,
+    Progress {
+        words: usize,
+        wpm: f64,
+    },
+    Finish {
+        wpm: f64,
+        accuracy: f64,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Confirms joining the room, `id` identifies the player in further messages
+    Joined {
+        id: u32,
+        layout: String,
+        side: TypingSide,
+    },
+    /// Players of the room, sent whenever someone joins or leaves
+    Players {
+        players: Vec<Player>,
+    },
+    Start {
+        seed: u64,
+        words: Vec<String>,
+    },
+    Progress {
+        id: u32,
+        words: usize,
+        wpm: f64,
+    },
+    Finished {
+        id: u32,
+        wpm: f64,
+        accuracy: f64,
+        place: usize,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Player {
+    pub id: u32,
+    pub name: String,
+}
+
+/// Picks [`RACE_WORDS`] words from `side` of `dictionary`, the same for the same `seed`
+pub fn race_words(dictionary: &LayoutDictionary, side: TypingSide, seed: u64) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    dictionary
+        .side(side)
+        .words()
+        .choose_multiple(&mut rng, RACE_WORDS)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,1017 @@
+use crate::keys::{LEFT_CODES, RIGHT_CODES};
+use keyboard_types::Code;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    vec::Vec,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use web_time::Instant;
+
+/// Stores dictionaries of words and keys they consist of.
+///
+/// # Note
+/// `keys` is expected to be a whitespace-separated sequence of key rows in any script,
+/// which are laid over physical keys of the half by [`KeyMap`](crate::keys::KeyMap)
+/// and shown in uppercase where the script has one
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WordDictionary {
+    words: Vec<String>,
+    keys: String,
+}
+
+impl WordDictionary {
+    pub(crate) fn new(words: Vec<String>, keys: String) -> Self {
+        WordDictionary { words, keys }
+    }
+
+    pub fn keys(&self) -> &str {
+        self.keys.as_ref()
+    }
+
+    pub fn words(&self) -> &[String] {
+        self.words.as_ref()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LayoutDictionary {
+    pub left: WordDictionary,
+    pub right: WordDictionary,
+}
+
+impl LayoutDictionary {
+    pub fn side(&self, side: TypingSide) -> &WordDictionary {
+        match side {
+            TypingSide::Left => &self.left,
+            TypingSide::Right => &self.right,
+        }
+    }
+}
+
+impl Default for LayoutDictionary {
+    fn default() -> Self {
+        LayoutDictionary {
+            left: WordDictionary {
+                words: vec!["<space>".to_owned()],
+                keys: "QWERT ASDFG ZXCVB".to_owned(),
+            },
+            right: WordDictionary {
+                words: vec!["<space>".to_owned()],
+                keys: "YUIOP HJKL; NM,./".to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Layouts {
+    pub qwerty: LayoutDictionary,
+    pub colemak: LayoutDictionary,
+    pub dvorak: LayoutDictionary,
+    pub workman: LayoutDictionary,
+    /// Russian ЙЦУКЕН
+    pub jcuken: LayoutDictionary,
+}
+
+impl Layouts {
+    /// Parses the dictionaries bundled with Hemi in `assets/words.json`
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("../../assets/words.json"))
+            .expect("Bundled dictionaries are malformed")
+    }
+
+    /// Returns dictionary of the layout called `name`
+    pub fn get(&self, name: &str) -> Option<&LayoutDictionary> {
+        match name {
+            "qwerty" => Some(&self.qwerty),
+            "colemak" => Some(&self.colemak),
+            "dvorak" => Some(&self.dvorak),
+            "workman" => Some(&self.workman),
+            "jcuken" => Some(&self.jcuken),
+            _ => None,
+        }
+    }
+
+    /// Words of both halves of every layout, repeating the ones shared between them
+    pub fn words(&self) -> impl Iterator<Item = &String> {
+        [
+            &self.qwerty,
+            &self.colemak,
+            &self.dvorak,
+            &self.workman,
+            &self.jcuken,
+        ]
+        .into_iter()
+        .flat_map(|layout| [&layout.left, &layout.right])
+        .flat_map(|half| half.words())
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypingSide {
+    #[default]
+    Left,
+    Right,
+}
+
+impl TypingSide {
+    pub fn flipped(self) -> Self {
+        match self {
+            TypingSide::Left => TypingSide::Right,
+            TypingSide::Right => TypingSide::Left,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypingSide::Left => "left",
+            TypingSide::Right => "right",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            TypingSide::Left => 0,
+            TypingSide::Right => 1,
+        }
+    }
+
+    /// Returns the half of a row-staggered keyboard the physical key `code` is located on.
+    ///
+    /// # Note
+    /// Browsers report the same [`Code::Space`] for both thumbs, so it belongs to neither side
+    pub fn of_code(code: &Code) -> Option<Self> {
+        if LEFT_CODES.iter().any(|row| row.contains(code)) {
+            Some(TypingSide::Left)
+        } else if RIGHT_CODES.iter().any(|row| row.contains(code)) {
+            Some(TypingSide::Right)
+        } else {
+            None
+        }
+    }
+}
+
+/// Defines how keystrokes from outside the active half are treated
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrictMode {
+    /// Keys from both halves are accepted
+    #[default]
+    Off,
+    /// Keys from the wrong half are accepted, but the word counts as missed
+    Flag,
+    /// Keys from the wrong half are counted as errors and never reach the input
+    Block,
+}
+
+/// Defines when words switch to the other side of the keyboard within one session
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SideSwitch {
+    /// All words are taken from the selected side
+    #[default]
+    Never,
+    /// Side is flipped after every `n` words
+    Words(usize),
+    /// Side is flipped after a "sentence" of 4 to 8 words
+    Sentence,
+}
+
+impl SideSwitch {
+    fn run_length(&self) -> usize {
+        match self {
+            SideSwitch::Never => usize::MAX,
+            SideSwitch::Words(n) => (*n).max(1),
+            SideSwitch::Sentence => rand::thread_rng().gen_range(4..=8),
+        }
+    }
+}
+
+/// Defines how mistakes can be corrected while typing
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputPolicy {
+    /// Any character is accepted and can be erased with Backspace
+    #[default]
+    Free,
+    /// Incorrect characters are rejected until the right key is pressed
+    StopOnError,
+    /// Backspace is disabled and errors are final
+    NoCorrection,
+}
+
+/// Defines what happens to words typed incorrectly
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryMode {
+    /// Missed words are dropped
+    #[default]
+    Off,
+    /// Missed words come back [`RETRY_DELAY`] words later until typed correctly
+    Later,
+    /// Missed words have to be retyped right away until typed correctly
+    Immediate,
+}
+
+/// Amount of words submitted before a missed word comes back in [`RetryMode::Later`]
+const RETRY_DELAY: usize = 3;
+
+/// Amount of words generated at once and kept ahead of the input
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferSize {
+    /// Words appended to the buffer per refill
+    pub batch: usize,
+    /// Buffer is refilled once no more than this amount of words is left
+    pub lookahead: usize,
+}
+
+impl Default for BufferSize {
+    fn default() -> Self {
+        BufferSize {
+            batch: 10,
+            lookahead: 3,
+        }
+    }
+}
+
+/// Word to be typed along with the side it belongs to
+#[derive(Clone)]
+pub struct Word {
+    text: String,
+    side: TypingSide,
+}
+
+/// Typing results of a single hand
+#[derive(Default, Clone, Copy)]
+pub struct HandStats {
+    correct: u32,
+    missed: u32,
+    wrong_hand: u32,
+    keystrokes: u32,
+    errors: u32,
+    chars: usize,
+    seconds: f64,
+    streak: i32,
+    best_streak: i32,
+}
+
+impl HandStats {
+    pub fn correct(&self) -> u32 {
+        self.correct
+    }
+
+    pub fn missed(&self) -> u32 {
+        self.missed
+    }
+
+    /// Percentage of correct keystrokes
+    pub fn accuracy(&self) -> f64 {
+        accuracy(self.keystrokes, self.errors)
+    }
+
+    /// Words per minute over the words typed with this hand, if any
+    pub fn wpm(&self) -> Option<f64> {
+        (self.seconds > 0.0).then(|| wpm(self.chars, self.seconds))
+    }
+
+    pub fn wrong_hand(&self) -> u32 {
+        self.wrong_hand
+    }
+
+    pub fn best_streak(&self) -> i32 {
+        self.best_streak
+    }
+}
+
+/// Amount of words in a single typing session
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestLength {
+    /// Session never ends
+    #[default]
+    Endless,
+    /// Session ends after `n` submitted words
+    Words(usize),
+}
+
+/// Results of a finished typing session
+#[derive(Clone, Copy)]
+pub struct SessionResult {
+    wpm: f64,
+    accuracy: f64,
+    hands: [Option<f64>; 2],
+}
+
+impl SessionResult {
+    pub fn wpm(&self) -> f64 {
+        self.wpm
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        self.accuracy
+    }
+
+    /// Words per minute of the hand on `side`, if it typed any words
+    pub fn hand_wpm(&self, side: TypingSide) -> Option<f64> {
+        self.hands[side.index()]
+    }
+}
+
+/// Words per minute for `chars` typed in `seconds`, counting five characters as a word
+fn wpm(chars: usize, seconds: f64) -> f64 {
+    if seconds > 0.0 {
+        chars as f64 / 5.0 * 60.0 / seconds
+    } else {
+        0.0
+    }
+}
+
+fn accuracy(keystrokes: u32, errors: u32) -> f64 {
+    match keystrokes {
+        0 => 100.0,
+        keystrokes => 100.0 * keystrokes.saturating_sub(errors) as f64 / keystrokes as f64,
+    }
+}
+
+/// Notable moment of a session, for front ends to give feedback on
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum TypingEvent {
+    /// Wrong character typed, keystroke from the wrong hand, or word submitted with mistakes
+    Mistake,
+    /// Word submitted correctly, extending the streak to the given length
+    Streak(i32),
+    Finished,
+}
+
+/// Stores data for typing panel
+#[derive(Default, Clone)]
+pub struct TypingData {
+    input: String,
+    streak: i32,
+    last_word: String,
+    words: Vec<Word>,
+    side: TypingSide,
+    switch: SideSwitch,
+    run: usize,
+    strict: StrictMode,
+    flagged: bool,
+    policy: InputPolicy,
+    retry: RetryMode,
+    retries: VecDeque<(usize, Word)>,
+    /// Words taken from the buffer during the session, to restart it with
+    typed: Vec<Word>,
+    buffer_size: BufferSize,
+    length: TestLength,
+    /// Settings of the user put aside while a session runs on given words
+    held: Option<(SideSwitch, RetryMode, TestLength)>,
+    started: Option<Instant>,
+    last_submit: Option<Instant>,
+    position: usize,
+    submitted: usize,
+    result: Option<SessionResult>,
+    stats: [HandStats; 2],
+    /// Events raised by the last submit or push
+    events: Vec<TypingEvent>,
+}
+
+impl TypingData {
+    /// Generates the first batch of `buffer_size` from provided `dictionary`,
+    /// starting on `side` and switching sides according to `switch`
+    pub fn new(
+        buffer_size: BufferSize,
+        dictionary: &LayoutDictionary,
+        side: TypingSide,
+        switch: SideSwitch,
+    ) -> Self {
+        let mut data = TypingData {
+            side,
+            switch,
+            buffer_size,
+            ..Default::default()
+        };
+        data.refill(dictionary);
+        data
+    }
+
+    pub fn submit(&mut self) {
+        self.events.clear();
+
+        if self.result.is_some() {
+            self.reset_session();
+            return;
+        }
+
+        if self.policy == InputPolicy::StopOnError
+            && self
+                .next_word()
+                .is_some_and(|word| word != self.input.trim())
+        {
+            // The rejected submit is a wrong keystroke like any other
+            let stats = &mut self.stats[self.active_side().index()];
+            stats.keystrokes += 1;
+            stats.errors += 1;
+            self.events.push(TypingEvent::Mistake);
+            return;
+        }
+
+        self.last_word = self.input.clone();
+        if let Some(word) = self.take_current() {
+            let now = Instant::now();
+            let since = self.last_submit.unwrap_or(now);
+            let length = word.text.chars().count() + 1;
+            let stats = &mut self.stats[word.side.index()];
+
+            stats.seconds += now.duration_since(since).as_secs_f64();
+            self.started.get_or_insert(since);
+            self.last_submit = Some(now);
+            self.position += length;
+            self.submitted += 1;
+
+            if self.input.trim() == word.text && !self.flagged {
+                stats.chars += length;
+                self.streak += 1;
+                stats.correct += 1;
+                stats.streak += 1;
+                stats.best_streak = stats.best_streak.max(stats.streak);
+                self.events.push(TypingEvent::Streak(self.streak));
+            } else {
+                self.streak = 0;
+                stats.missed += 1;
+                stats.streak = 0;
+                self.events.push(TypingEvent::Mistake);
+                self.queue_retry(word);
+            }
+        }
+
+        self.input.clear();
+        self.flagged = false;
+
+        if let TestLength::Words(amount) = self.length {
+            if self.submitted >= amount {
+                self.finish();
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        self.result = Some(SessionResult {
+            wpm: self.wpm(),
+            accuracy: self.accuracy(),
+            hands: self.stats.map(|stats| stats.wpm()),
+        });
+        self.events.push(TypingEvent::Finished);
+        self.release_words();
+    }
+
+    /// Clears all session counters and the timer, keeping the words buffer
+    pub fn reset_session(&mut self) {
+        let words = std::mem::take(&mut self.words);
+        let retries = std::mem::take(&mut self.retries);
+
+        *self = TypingData {
+            words,
+            retries,
+            side: self.side,
+            switch: self.switch,
+            run: self.run,
+            strict: self.strict,
+            policy: self.policy,
+            retry: self.retry,
+            buffer_size: self.buffer_size,
+            length: self.length,
+            held: self.held,
+            ..Default::default()
+        };
+    }
+
+    /// Restarts the session on the same words it started with, clearing all its counters
+    pub fn restart(&mut self) {
+        let mut words = std::mem::take(&mut self.typed);
+        words.append(&mut self.words);
+        self.words = words;
+        self.retries.clear();
+        self.reset_session();
+    }
+
+    /// Starts a new session on fresh words from `dictionary`, on the side the current one started
+    pub fn new_test(&mut self, dictionary: &LayoutDictionary) {
+        if let Some(word) = self.typed.first().or(self.words.first()) {
+            self.side = word.side;
+        }
+
+        self.drain();
+        self.run = 0;
+        self.reset_session();
+        self.refill(dictionary);
+    }
+
+    /// Results of the session, once it's finished
+    pub fn result(&self) -> Option<&SessionResult> {
+        self.result.as_ref()
+    }
+
+    /// Words per minute over correctly typed words of the session
+    pub fn wpm(&self) -> f64 {
+        let chars = self.stats.iter().map(|stats| stats.chars).sum();
+        wpm(chars, self.elapsed())
+    }
+
+    /// Seconds passed since the first keystroke of the session
+    pub fn elapsed(&self) -> f64 {
+        match (self.started, self.result) {
+            (Some(started), None) => started.elapsed().as_secs_f64(),
+            (Some(started), Some(_)) => self
+                .last_submit
+                .map_or(0.0, |last| last.duration_since(started).as_secs_f64()),
+            (None, _) => 0.0,
+        }
+    }
+
+    /// Characters a ghost typing at `wpm` since the start of the session is ahead of the input,
+    /// counted from the start of the current word
+    pub fn ghost(&self, wpm: f64) -> Option<f64> {
+        self.started?;
+        if self.result.is_some() {
+            return None;
+        }
+
+        Some(self.elapsed() / 60.0 * wpm * 5.0 - self.position as f64)
+    }
+
+    pub fn last_word(&self) -> &str {
+        self.last_word.as_ref()
+    }
+
+    pub fn next_word(&self) -> Option<&str> {
+        self.current().map(|word| word.text.as_str())
+    }
+
+    /// Side of the word currently being typed
+    pub fn active_side(&self) -> TypingSide {
+        self.current().map_or(self.side, |word| word.side)
+    }
+
+    /// Word currently being typed, retries that are due come before the buffer
+    fn current(&self) -> Option<&Word> {
+        match self.retries.front() {
+            Some((0, word)) => Some(word),
+            _ => self.words.first(),
+        }
+    }
+
+    fn take_current(&mut self) -> Option<Word> {
+        let word = match self.retries.front() {
+            Some((0, _)) => self.retries.pop_front().map(|(_, word)| word),
+            _ if !self.words.is_empty() => {
+                let word = self.words.remove(0);
+                self.typed.push(word.clone());
+                Some(word)
+            }
+            _ => None,
+        };
+
+        for (delay, _) in self.retries.iter_mut() {
+            *delay = delay.saturating_sub(1);
+        }
+
+        word
+    }
+
+    fn queue_retry(&mut self, word: Word) {
+        match self.retry {
+            RetryMode::Off => (),
+            RetryMode::Later => self.retries.push_back((RETRY_DELAY, word)),
+            RetryMode::Immediate => self.retries.push_front((0, word)),
+        }
+    }
+
+    /// Amount of missed words waiting to be retyped
+    pub fn retries(&self) -> usize {
+        self.retries.len()
+    }
+
+    /// Pushes characters of `string` one by one, scoring each against the current word
+    pub fn push_str(&mut self, string: &str) {
+        self.events.clear();
+        self.push_chars(string);
+    }
+
+    fn push_chars(&mut self, string: &str) {
+        if self.result.is_some() {
+            return;
+        }
+
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            self.last_submit = self.started;
+        }
+
+        for char in string.chars() {
+            let mut typed = self.input.clone();
+            typed.push(char);
+
+            let correct = self
+                .next_word()
+                .is_some_and(|word| word.starts_with(&typed));
+            let stats = &mut self.stats[self.active_side().index()];
+            stats.keystrokes += 1;
+
+            if !correct {
+                stats.errors += 1;
+                self.events.push(TypingEvent::Mistake);
+                if self.policy == InputPolicy::StopOnError {
+                    continue;
+                }
+            }
+
+            self.input = typed;
+        }
+    }
+
+    /// Pushes `string` typed on the physical half `side`, enforcing the active hand in strict mode
+    pub fn push_str_from(&mut self, string: &str, side: Option<TypingSide>) {
+        self.events.clear();
+        let active = self.active_side();
+        let wrong_hand = side.is_some_and(|side| side != active);
+
+        if self.strict != StrictMode::Off && wrong_hand {
+            let stats = &mut self.stats[active.index()];
+            stats.wrong_hand += 1;
+            stats.streak = 0;
+            self.streak = 0;
+            self.events.push(TypingEvent::Mistake);
+
+            // Blocked keys never reach the word, which can still be typed right
+            if self.strict == StrictMode::Block {
+                stats.keystrokes += 1;
+                stats.errors += 1;
+                return;
+            }
+
+            self.flagged = true;
+        }
+
+        self.push_chars(string);
+    }
+
+    pub fn input(&self) -> &str {
+        self.input.as_ref()
+    }
+
+    /// Erases the last typed character along with the marks combined with it,
+    /// unless corrections are disabled by the [`InputPolicy`]
+    pub fn pop(&mut self) -> Option<String> {
+        if self.policy == InputPolicy::NoCorrection {
+            return None;
+        }
+
+        let (start, last) = self.input.grapheme_indices(true).next_back()?;
+        let last = last.to_owned();
+        self.input.truncate(start);
+        Some(last)
+    }
+
+    pub fn buffer(&self) -> &Vec<Word> {
+        self.words.as_ref()
+    }
+
+    pub fn drain(&mut self) {
+        self.words.drain(..);
+        self.retries.clear();
+    }
+
+    /// Drains the buffer and starts the next words from `side`
+    pub fn set_side(&mut self, side: TypingSide) {
+        self.drain();
+        self.side = side;
+        self.run = 0;
+    }
+
+    /// Drains the buffer and applies new side `switch` starting from the current side
+    pub fn set_switch(&mut self, switch: SideSwitch) {
+        let side = self.active_side();
+        self.switch = switch;
+        self.set_side(side);
+    }
+
+    pub fn switch(&self) -> SideSwitch {
+        self.switch
+    }
+
+    pub fn set_strict(&mut self, strict: StrictMode) {
+        self.strict = strict;
+    }
+
+    pub fn strict(&self) -> StrictMode {
+        self.strict
+    }
+
+    pub fn set_policy(&mut self, policy: InputPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn set_buffer_size(&mut self, buffer_size: BufferSize) {
+        self.buffer_size = buffer_size;
+    }
+
+    pub fn buffer_size(&self) -> BufferSize {
+        self.buffer_size
+    }
+
+    /// Replaces the buffer with `words` typed on `side` and starts a session of exactly these words,
+    /// as used by races, until it finishes or [`TypingData::release_words`] is called
+    pub fn set_words(&mut self, words: Vec<String>, side: TypingSide) {
+        self.held
+            .get_or_insert((self.switch, self.retry, self.length));
+        self.set_side(side);
+        self.switch = SideSwitch::Never;
+        self.retry = RetryMode::Off;
+        self.length = TestLength::Words(words.len());
+        self.words = words.into_iter().map(|text| Word { text, side }).collect();
+        self.reset_session();
+    }
+
+    /// Gives back the settings put aside by [`TypingData::set_words`], keeping the current session
+    pub fn release_words(&mut self) {
+        if let Some((switch, retry, length)) = self.held.take() {
+            self.switch = switch;
+            self.retry = retry;
+            self.length = length;
+        }
+    }
+
+    /// Amount of words submitted in the session
+    pub fn submitted(&self) -> usize {
+        self.submitted
+    }
+
+    /// Sets the length of the sessions, restarting the current one
+    pub fn set_length(&mut self, length: TestLength) {
+        self.length = length;
+        self.reset_session();
+    }
+
+    pub fn length(&self) -> TestLength {
+        self.length
+    }
+
+    pub fn set_retry(&mut self, retry: RetryMode) {
+        self.retry = retry;
+    }
+
+    /// Percentage of correct keystrokes of both hands
+    pub fn accuracy(&self) -> f64 {
+        let keystrokes: u32 = self.stats.iter().map(|stats| stats.keystrokes).sum();
+        let errors: u32 = self.stats.iter().map(|stats| stats.errors).sum();
+
+        accuracy(keystrokes, errors)
+    }
+
+    /// Events raised by the last call to [`Self::submit`], [`Self::push_str`] or [`Self::push_str_from`]
+    pub fn events(&self) -> &[TypingEvent] {
+        &self.events
+    }
+
+    pub fn streak(&self) -> i32 {
+        self.streak
+    }
+
+    pub fn stats(&self, side: TypingSide) -> &HandStats {
+        &self.stats[side.index()]
+    }
+
+    /// Appends a batch of words once no more than `lookahead` words are left in the buffer
+    pub fn refill(&mut self, dictionary: &LayoutDictionary) {
+        if self.words.len() <= self.buffer_size.lookahead {
+            self.generate_words(self.buffer_size.batch, dictionary);
+        }
+    }
+
+    /// Appends `amount` of words from `dictionary` to the buffer, avoiding words already in it
+    pub fn generate_words(&mut self, amount: usize, dictionary: &LayoutDictionary) {
+        let mut rng = rand::thread_rng();
+        let mut generated = 0;
+
+        while generated < amount {
+            if self.run == 0 {
+                self.run = self.switch.run_length();
+            }
+
+            let side = self.side;
+            let count = self.run.min(amount - generated);
+            let source = dictionary.side(side).words();
+            if source.is_empty() {
+                break;
+            }
+
+            let words: Vec<Word> = {
+                let buffered: HashSet<&str> =
+                    self.words.iter().map(|word| word.text.as_str()).collect();
+                let last = self.words.last().map(|word| word.text.as_str());
+
+                let fresh: Vec<&String> = source
+                    .iter()
+                    .filter(|word| !buffered.contains(word.as_str()))
+                    .collect();
+                let pool = if fresh.len() >= count || source.len() < 2 {
+                    fresh
+                } else {
+                    source
+                        .iter()
+                        .filter(|&word| Some(word.as_str()) != last)
+                        .collect()
+                };
+
+                pool.choose_multiple(&mut rng, count)
+                    .map(|text| Word {
+                        text: text.to_string(),
+                        side,
+                    })
+                    .collect()
+            };
+
+            self.words.extend(words);
+            generated += count;
+
+            self.run -= count;
+            if self.run == 0 {
+                self.side = self.side.flipped();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Slurs that word lists generated from the bundled ones must not carry over
+    const BLOCKLIST: &[&str] = &["homo", "honky", "kike", "twat"];
+
+    fn dictionary() -> LayoutDictionary {
+        let half = |words: &[&str], keys: &str| {
+            WordDictionary::new(
+                words.iter().map(|&word| word.to_owned()).collect(),
+                keys.to_owned(),
+            )
+        };
+
+        LayoutDictionary {
+            left: half(&["as", "we", "tax", "sea", "vet"], "QWERT ASDFG ZXCVB"),
+            right: half(&["oh", "hip", "joy", "pun", "mop"], "YUIOP HJKL; NM,./"),
+        }
+    }
+
+    fn texts(typer: &TypingData) -> Vec<String> {
+        typer
+            .buffer()
+            .iter()
+            .map(|word| word.text.clone())
+            .collect()
+    }
+
+    /// Types the current word, right if `correct`
+    fn type_word(typer: &mut TypingData, correct: bool) {
+        let word = typer.next_word().unwrap().to_owned();
+        typer.push_str(if correct { &word } else { "x" });
+        typer.submit();
+    }
+
+    #[test]
+    fn refill_appends_behind_queued_words() {
+        let buffer = BufferSize {
+            batch: 4,
+            lookahead: 2,
+        };
+        let mut typer = TypingData::new(buffer, &dictionary(), TypingSide::Left, SideSwitch::Never);
+        let queued = texts(&typer);
+        assert_eq!(queued.len(), 4);
+
+        // Nothing is appended while more than `lookahead` words are left
+        type_word(&mut typer, true);
+        typer.refill(&dictionary());
+        assert_eq!(texts(&typer), queued[1..]);
+
+        type_word(&mut typer, true);
+        typer.refill(&dictionary());
+        let buffer = texts(&typer);
+        assert_eq!(buffer.len(), 6);
+        assert_eq!(buffer[..2], queued[2..]);
+
+        for _ in 0..20 {
+            typer.generate_words(4, &dictionary());
+        }
+        let buffer = texts(&typer);
+        assert!(
+            buffer.windows(2).all(|pair| pair[0] != pair[1]),
+            "{buffer:?}"
+        );
+    }
+
+    #[test]
+    fn missed_words_come_back_later() {
+        let mut typer = TypingData::new(
+            BufferSize::default(),
+            &dictionary(),
+            TypingSide::Left,
+            SideSwitch::Never,
+        );
+        typer.set_retry(RetryMode::Later);
+        let missed = typer.next_word().unwrap().to_owned();
+
+        type_word(&mut typer, false);
+        assert_eq!(typer.retries(), 1);
+        for _ in 0..RETRY_DELAY {
+            assert_ne!(typer.next_word(), Some(missed.as_str()));
+            type_word(&mut typer, true);
+        }
+
+        assert_eq!(typer.next_word(), Some(missed.as_str()));
+        type_word(&mut typer, true);
+        assert_eq!(typer.retries(), 0);
+    }
+
+    #[test]
+    fn sides_switch_every_n_words() {
+        let typer = TypingData::new(
+            BufferSize::default(),
+            &dictionary(),
+            TypingSide::Left,
+            SideSwitch::Words(2),
+        );
+
+        let sides: Vec<TypingSide> = typer.buffer().iter().map(|word| word.side).collect();
+        let expected: Vec<TypingSide> = (0..sides.len())
+            .map(|i| match i / 2 % 2 {
+                0 => TypingSide::Left,
+                _ => TypingSide::Right,
+            })
+            .collect();
+        assert_eq!(sides, expected);
+    }
+
+    #[test]
+    fn restart_replays_the_same_words() {
+        let mut typer = TypingData::new(
+            BufferSize::default(),
+            &dictionary(),
+            TypingSide::Left,
+            SideSwitch::Never,
+        );
+        let words = texts(&typer);
+
+        type_word(&mut typer, true);
+        type_word(&mut typer, false);
+        typer.restart();
+
+        assert_eq!(texts(&typer), words);
+        assert_eq!(typer.submitted(), 0);
+        assert_eq!(typer.stats(TypingSide::Left).correct(), 0);
+    }
+
+    #[test]
+    fn bundled_words_skip_blocklist() {
+        let layouts = Layouts::bundled();
+        let blocked: Vec<&String> = layouts
+            .words()
+            .filter(|word| BLOCKLIST.contains(&word.to_lowercase().as_str()))
+            .collect();
+
+        assert!(blocked.is_empty(), "blocklisted words: {blocked:?}");
+    }
+
+    #[test]
+    fn rejected_submit_counts_as_keystroke() {
+        let mut typer = TypingData::default();
+        typer.set_policy(InputPolicy::StopOnError);
+        typer.set_words(vec!["as".to_owned()], TypingSide::Left);
+
+        typer.push_str("ax");
+        typer.submit();
+        typer.submit();
+
+        // One correct keystroke out of two typed and two rejected submits
+        assert_eq!(typer.accuracy(), 25.0);
+        assert_eq!(typer.input(), "a");
+    }
+
+    #[test]
+    fn block_counts_wrong_hand_as_error() {
+        let mut typer = TypingData::default();
+        typer.set_strict(StrictMode::Block);
+        typer.set_words(vec!["as".to_owned()], TypingSide::Left);
+
+        typer.push_str_from("a", Some(TypingSide::Left));
+        typer.push_str_from("l", Some(TypingSide::Right));
+        typer.push_str_from("s", Some(TypingSide::Left));
+        typer.submit();
+
+        let stats = typer.stats(TypingSide::Left);
+        assert_eq!(typer.input(), "");
+        assert_eq!((stats.correct(), stats.missed()), (1, 0));
+        assert!(stats.accuracy() < 100.0);
+    }
+}
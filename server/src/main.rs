@@ -0,0 +1,250 @@
+//! Self-hostable race server, see [`hemi_core::protocol`] for the protocol.
+//!
+//! Listens on `127.0.0.1:8000` by default, pass another address as the first argument to override.
+
+use futures_util::{SinkExt, StreamExt};
+use hemi_core::protocol::{race_words, ClientMessage, Player, ServerMessage, RACE_PATH};
+use hemi_core::words::{Layouts, TypingSide};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+struct Peer {
+    name: String,
+    sender: UnboundedSender<Message>,
+}
+
+struct Room {
+    layout: String,
+    side: TypingSide,
+    players: HashMap<u32, Peer>,
+    /// Players who finished the current race, in the order they did
+    finished: Vec<u32>,
+}
+
+impl Room {
+    fn broadcast(&self, message: &ServerMessage) {
+        for peer in self.players.values() {
+            send(&peer.sender, message);
+        }
+    }
+
+    fn players(&self) -> ServerMessage {
+        let mut players: Vec<Player> = self
+            .players
+            .iter()
+            .map(|(&id, peer)| Player {
+                id,
+                name: peer.name.clone(),
+            })
+            .collect();
+        players.sort_by_key(|player| player.id);
+
+        ServerMessage::Players { players }
+    }
+}
+
+struct Server {
+    layouts: Layouts,
+    rooms: Mutex<HashMap<String, Room>>,
+    next_id: AtomicU32,
+}
+
+fn send(sender: &UnboundedSender<Message>, message: &ServerMessage) {
+    let text = serde_json::to_string(message).expect("Server messages are serializable");
+    let _ = sender.send(Message::text(text));
+}
+
+#[tokio::main]
+async fn main() {
+    let addr: SocketAddr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:8000".to_owned())
+        .parse()
+        .expect("Invalid address to listen on");
+
+    let listener = TcpListener::bind(addr).await.expect("Failed to bind");
+    println!("hemi-server listening on ws://{addr}{RACE_PATH}");
+
+    let server = Arc::new(Server {
+        layouts: Layouts::bundled(),
+        rooms: Mutex::new(HashMap::new()),
+        next_id: AtomicU32::new(1),
+    });
+
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(connect(server.clone(), stream));
+    }
+}
+
+/// Rejects WebSocket handshakes outside of [`RACE_PATH`]
+#[allow(clippy::result_large_err)]
+fn check_path(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    if request.uri().path() == RACE_PATH {
+        Ok(response)
+    } else {
+        let mut error = ErrorResponse::new(None);
+        *error.status_mut() = StatusCode::NOT_FOUND;
+        Err(error)
+    }
+}
+
+async fn connect(server: Arc<Server>, stream: TcpStream) {
+    let Ok(socket) = tokio_tungstenite::accept_hdr_async(stream, check_path).await else {
+        return;
+    };
+
+    let (mut sink, mut stream) = socket.split();
+    let (sender, mut receiver) = unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let id = server.next_id.fetch_add(1, Ordering::Relaxed);
+    let mut room = None;
+
+    while let Some(Ok(message)) = stream.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+
+        match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => server.handle(id, &mut room, &sender, message),
+            Err(err) => send(
+                &sender,
+                &ServerMessage::Error {
+                    message: err.to_string(),
+                },
+            ),
+        }
+    }
+
+    if let Some(name) = room {
+        server.leave(id, &name);
+    }
+}
+
+impl Server {
+    fn handle(
+        &self,
+        id: u32,
+        joined: &mut Option<String>,
+        sender: &UnboundedSender<Message>,
+        message: ClientMessage,
+    ) {
+        let error = |message: &str| {
+            send(
+                sender,
+                &ServerMessage::Error {
+                    message: message.to_owned(),
+                },
+            )
+        };
+
+        let mut rooms = self.rooms.lock().unwrap();
+
+        if let ClientMessage::Join {
+            room: name,
+            name: player,
+            layout,
+            side,
+        } = message
+        {
+            if joined.is_some() {
+                return error("already joined a room");
+            }
+            if self.layouts.get(&layout).is_none() {
+                return error("unknown layout");
+            }
+
+            let room = rooms.entry(name.clone()).or_insert_with(|| Room {
+                layout,
+                side,
+                players: HashMap::new(),
+                finished: Vec::new(),
+            });
+
+            room.players.insert(
+                id,
+                Peer {
+                    name: player,
+                    sender: sender.clone(),
+                },
+            );
+
+            send(
+                sender,
+                &ServerMessage::Joined {
+                    id,
+                    layout: room.layout.clone(),
+                    side: room.side,
+                },
+            );
+            room.broadcast(&room.players());
+            *joined = Some(name);
+            return;
+        }
+
+        let Some(room) = joined.as_ref().and_then(|name| rooms.get_mut(name)) else {
+            return error("join a room first");
+        };
+
+        match message {
+            ClientMessage::Join { .. } => (),
+            ClientMessage::Start => {
+                let seed = rand::random();
+                let Some(dictionary) = self.layouts.get(&room.layout) else {
+                    return error("unknown layout");
+                };
+
+                room.finished.clear();
+                room.broadcast(&ServerMessage::Start {
+                    seed,
+                    words: race_words(dictionary, room.side, seed),
+                });
+            }
+            ClientMessage::Progress { words, wpm } => {
+                room.broadcast(&ServerMessage::Progress { id, words, wpm });
+            }
+            ClientMessage::Finish { wpm, accuracy } => {
+                // Each player finishes a race once
+                if room.finished.contains(&id) {
+                    return;
+                }
+
+                room.finished.push(id);
+                room.broadcast(&ServerMessage::Finished {
+                    id,
+                    wpm,
+                    accuracy,
+                    place: room.finished.len(),
+                });
+            }
+        }
+    }
+
+    fn leave(&self, id: u32, name: &str) {
+        let mut rooms = self.rooms.lock().unwrap();
+        let Some(room) = rooms.get_mut(name) else {
+            return;
+        };
+
+        room.players.remove(&id);
+        if room.players.is_empty() {
+            rooms.remove(name);
+        } else {
+            room.broadcast(&room.players());
+        }
+    }
+}
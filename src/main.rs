@@ -1,79 +1,154 @@
 #![allow(non_snake_case)]
 
+mod race;
+mod settings;
+mod shortcuts;
+mod sound;
+mod storage;
+mod theme;
 mod words;
 
 use dioxus::document::Stylesheet;
-use dioxus::events::{KeyboardData, MouseEvent};
+use dioxus::events::{CompositionData, KeyboardData, MouseEvent};
 use dioxus::html::input_data::keyboard_types::{Code, Key};
 use dioxus::prelude::*;
+use hemi_core::keys::*;
+use hemi_core::words::*;
+use race::*;
+use settings::*;
+use shortcuts::*;
+use sound::*;
+use storage::*;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
 use words::*;
 
-#[derive(Clone, Copy)]
+/// Words a hand types in an endless session before its speed counts towards the personal bests
+const ENDLESS_BEST_WORDS: u32 = 20;
+
+#[derive(Clone, Copy, PartialEq)]
 enum MainPanel {
     Typing,
     Info,
-}
-
-#[derive(Clone, Debug, Copy)]
-enum TypingSide {
-    Left,
-    Right,
-}
-
-#[derive(Clone, Debug, Copy)]
-enum KeyboardLayout {
-    Qwerty,
-    Dvorak,
-    Colemak,
-    Workman,
-    Custom,
-}
-
-#[derive(Clone)]
-pub(crate) struct AppSettings {
-    sound_enabled: bool,
-    status_enabled: bool,
-    keyboard_enabled: bool,
+    Race,
+    Settings,
 }
 
 #[derive(Clone)]
 pub(crate) struct AppState {
+    keymap: KeyMap,
     keyboard: KeyboardState,
     settings: AppSettings,
     typer: TypingData,
-    layout: KeyboardLayout,
     panel: MainPanel,
     side: TypingSide,
+    bests: PersonalBests,
+    /// Text being composed through a dead key or an IME, typed once the composition ends
+    composition: String,
 }
 
 impl AppState {
     pub(crate) fn refresh_keyboard(&mut self, dictionary: &LayoutDictionary) {
-        match self.side {
-            TypingSide::Left => {
-                self.keyboard = KeyboardState::new(&dictionary.left);
-            }
-            TypingSide::Right => {
-                self.keyboard = KeyboardState::new(&dictionary.right);
+        self.keymap = KeyMap::new(dictionary);
+        self.keyboard = KeyboardState::new(&self.keymap, self.side, self.settings.geometry);
+    }
+
+    /// Follows the side of the current word, refreshing the keyboard when it changes
+    pub(crate) fn sync_side(&mut self, dictionary: &LayoutDictionary) {
+        let side = self.typer.active_side();
+        if side != self.side {
+            self.side = side;
+            self.refresh_keyboard(dictionary);
+        }
+    }
+
+    /// Physical key of the next character to type, the submit key of the side once the word is complete
+    pub(crate) fn next_key(&self) -> Option<Code> {
+        let word = self.typer.next_word()?;
+        let input = self.typer.input();
+        if !word.starts_with(input) {
+            return None;
+        }
+
+        match word.chars().nth(input.chars().count()) {
+            Some(next) => self.keymap.code_of(next),
+            None => Some(self.settings.submit.code(self.side)),
+        }
+    }
+
+    /// Words per minute of the ghost caret, if pacing is enabled
+    pub(crate) fn pace_wpm(&self) -> Option<f64> {
+        match self.settings.pace {
+            Pace::Off => None,
+            Pace::Wpm(wpm) => Some(wpm as f64),
+            Pace::Best => self.bests.get(self.settings.layout.name(), self.side),
+        }
+    }
+
+    /// Records personal bests of each hand from the finished session, or from the run so far
+    /// of an endless one
+    pub(crate) fn record_bests(&mut self) {
+        let endless = self.typer.length() == TestLength::Endless;
+        let result = self.typer.result().copied();
+
+        for side in [TypingSide::Left, TypingSide::Right] {
+            let stats = self.typer.stats(side);
+            let wpm = match result {
+                Some(result) => result.hand_wpm(side),
+                None if endless && stats.correct() >= ENDLESS_BEST_WORDS => stats.wpm(),
+                None => None,
+            };
+
+            if let Some(wpm) = wpm {
+                self.bests.record(self.settings.layout.name(), side, wpm);
             }
-        };
+        }
     }
 
-    pub(crate) fn new(dict: &WordDictionary) -> Self {
+    pub(crate) fn new(dict: &LayoutDictionary, settings: AppSettings) -> Self {
+        let keymap = KeyMap::new(dict);
+        let mut typer = TypingData::new(settings.buffer, dict, settings.side, settings.switch);
+        typer.set_strict(settings.strict);
+        typer.set_policy(settings.policy);
+        typer.set_retry(settings.retry);
+        typer.set_length(settings.length);
+
         AppState {
-            keyboard: KeyboardState::new(dict),
-            typer: TypingData::new(10, dict),
+            keyboard: KeyboardState::new(&keymap, settings.side, settings.geometry),
+            keymap,
+            typer,
             panel: MainPanel::Typing,
-            side: TypingSide::Left,
-            settings: AppSettings {
-                sound_enabled: true,
-                status_enabled: true,
-                keyboard_enabled: true,
-            },
-            layout: KeyboardLayout::Qwerty,
+            side: settings.side,
+            settings,
+            bests: PersonalBests::load(),
+            composition: String::new(),
         }
     }
 }
 
+/// Hidden input keeping the focus, so that dead keys and IMEs can compose text into it
+const TYPING_INPUT: &str = "typing-input";
+
+/// Empties the typing input, which otherwise keeps the text of finished compositions
+fn clear_typing_input() {
+    let input = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(TYPING_INPUT))
+        .and_then(|element| element.dyn_into::<HtmlTextAreaElement>().ok());
+
+    if let Some(input) = input {
+        input.set_value("");
+    }
+}
+
+/// Whether keys currently go to the typing input, rather than to a control of some panel
+fn typing_input_focused() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.active_element())
+        .is_some_and(|element| element.id() == TYPING_INPUT)
+}
+
 fn main() {
     #[cfg(debug_assertions)]
     wasm_logger::init(wasm_logger::Config::new(log::Level::Debug));
@@ -81,88 +156,234 @@ fn main() {
 }
 
 fn App() -> Element {
-    use_context_provider(|| Signal::new(AudioLibrary::default()));
-    let mut audio = use_context::<Signal<AudioLibrary>>();
+    use_context_provider(|| Signal::new(WordSources::default()));
+    let mut sources = use_context::<Signal<WordSources>>();
 
-    use_context_provider(|| Signal::new(LayoutDictionary::default()));
+    // Settings are read from storage once, on mount
+    let settings = use_hook(AppSettings::load);
+    use_context_provider(|| Signal::new(settings.dictionary(&sources.read())));
     let mut dictionary = use_context::<Signal<LayoutDictionary>>();
 
-    use_context_provider(|| Signal::new(AppState::new(&dictionary.read().left)));
+    use_context_provider(|| Signal::new(AppState::new(&dictionary.read(), settings)));
     let mut app = use_context::<Signal<AppState>>();
 
-    use_context_provider(|| Signal::new(Layouts::default()));
-    let mut layouts_state = use_context::<Signal<Layouts>>();
+    use_context_provider(|| Signal::new(RaceState::default()));
+    let race = use_context::<Signal<RaceState>>();
+    let race_client = use_coroutine(move |commands| race_client(commands, race, app, dictionary));
 
-    let layouts = use_resource(|| async move { Layouts::pull().await });
+    let layouts = use_resource(|| async move { pull_layouts().await });
 
+    // Reloads the sound pack only when another one is selected, not on every settings change
+    let sound_pack = use_memo(move || app.read().settings.sound_pack.clone());
+    let audio = use_resource(move || async move { AudioLibrary::load(&sound_pack()).await });
+    let volume = use_memo(move || app.read().settings.volume);
     use_effect(move || {
-        if let Some(ref data) = *layouts.read() {
-            let mut app = app.write();
-            *layouts_state.write() = data.clone();
-
-            *dictionary.write() = match app.layout {
-                KeyboardLayout::Qwerty => data.qwerty.clone(),
-                KeyboardLayout::Colemak => data.colemak.clone(),
-                _ => data.qwerty.clone(),
-            };
+        if let Some(Some(audio)) = &*audio.read() {
+            audio.set_volume(volume());
+        }
+    });
 
-            app.refresh_keyboard(&dictionary.read());
+    use_effect(move || {
+        if let Some(Some(ref data)) = *layouts.read() {
+            sources.write().set_remote(data.clone());
 
-            let current_dict = match app.side {
-                TypingSide::Left => &dictionary.read().left,
-                TypingSide::Right => &dictionary.read().right,
-            };
-            app.typer = TypingData::new(10, current_dict);
+            if app.peek().settings.source == WordSource::Remote && !race.peek().is_racing() {
+                app.write().reload(&sources.peek(), &mut dictionary.write());
+            }
         }
     });
 
+    // Keys held down, for shortcuts chording them with another key
+    let mut held = use_signal(Vec::<Code>::new);
+
+    // Plays cues of the events raised by the last submit or push
+    let play_cues = move || {
+        let app = app.read();
+        let audio = audio.read();
+        let Some(Some(audio)) = &*audio else {
+            return;
+        };
+
+        if app.settings.sound_enabled {
+            let mut cues: Vec<Cue> = app
+                .typer
+                .events()
+                .iter()
+                .filter_map(|event| app.settings.cues.on(*event))
+                .collect();
+            cues.dedup();
+            cues.into_iter().for_each(|cue| audio.play_cue(cue));
+        }
+    };
+
     let on_key_down = move |event: Event<KeyboardData>| {
         let key_code = event.code();
+        // Lit on keydown, as modifiers, keys prevented from typing and dead keys never fire keypress
+        app.write().keyboard.update_for(&key_code, true);
+
+        // Dead keys and IME input are typed through composition events instead
+        if event.is_composing() || matches!(event.key(), Key::Dead | Key::Process) {
+            return;
+        }
+
+        let (action, holds) = {
+            let shortcuts = &app.read().settings.shortcuts;
+            (
+                shortcuts.action(&held.read(), key_code),
+                shortcuts.holds(key_code),
+            )
+        };
+        if !held.read().contains(&key_code) {
+            held.write().push(key_code);
+        }
+
+        // Prevents shortcut keys from being typed as well, through the following keypress
+        if let Some(action) = action {
+            event.prevent_default();
+            perform(action);
+            return;
+        } else if holds {
+            // Held keys such as Tab keep moving the focus between controls outside of the typer
+            if typing_input_focused() {
+                event.prevent_default();
+            }
+            return;
+        }
+
+        // Other panels keep their keys, for their inputs and buttons
+        if app.read().panel != MainPanel::Typing {
+            return;
+        }
+
+        let submits = {
+            let app = app.read();
+            app.settings.submit.submits(app.side, key_code)
+        };
 
         match key_code {
             Code::Backspace => {
                 app.write().typer.pop();
             }
-            Code::Space | Code::Enter => {
-                app.write().typer.submit();
+            _ if submits => {
+                // Keeps the submitting key from being typed, or from opening the browser menu
+                event.prevent_default();
+
+                let mut app = app.write();
+                let finished = app.typer.result().is_some();
+                app.typer.submit();
+
+                if !finished {
+                    app.record_bests();
+
+                    if race.read().is_racing() {
+                        race_client.send(RaceCommand::Send(report(&app.typer)));
+                    }
+                }
+
+                drop(app);
+                play_cues();
             }
             _ => (),
         }
 
-        if app.write().typer.buffer().len() <= 3 {
-            let app_dict = dictionary.read();
-            let mut app = app.write();
-            let dictionary = match app.side {
-                TypingSide::Left => &app_dict.left,
-                TypingSide::Right => &app_dict.right,
-            };
-            app.typer.generate_words(10, dictionary);
+        if !race.read().is_racing() {
+            app.write().typer.refill(&dictionary.read());
         }
 
+        app.write().sync_side(&dictionary.read());
+
         if app.write().settings.sound_enabled {
-            audio.write().play(key_code);
+            if let Some(Some(audio)) = &*audio.read() {
+                audio.play(key_code);
+            }
         }
     };
 
     let on_key_press = move |event: Event<KeyboardData>| {
+        if app.read().panel != MainPanel::Typing {
+            return;
+        }
+
+        // Keeps typed characters out of the typing input
+        event.prevent_default();
+        if event.is_composing() {
+            return;
+        }
+
         let key = &event.key();
-        app.write().keyboard.update_for(&KeyState::new(key, true));
+        let code = event.code();
+        let side = TypingSide::of_code(&code);
+        let mut app = app.write();
 
-        if let Key::Character(key) = key {
-            app.write().typer.push_str(&key.to_string());
+        let emulated = if app.settings.emulate_layout {
+            app.keymap.emulate(&code, event.modifiers().shift())
+        } else {
+            None
         };
+
+        if let Some(key) = emulated {
+            app.typer.push_str_from(&key.to_string(), side);
+        } else if let Key::Character(key) = key {
+            app.typer.push_str_from(key, side);
+        } else {
+            return;
+        };
+
+        drop(app);
+        play_cues();
+    };
+
+    let on_composition_update = move |event: Event<CompositionData>| {
+        app.write().composition = event.data().data();
+    };
+
+    let on_composition_end = move |event: Event<CompositionData>| {
+        let text = event.data().data();
+        let mut app = app.write();
+        app.composition.clear();
+        clear_typing_input();
+
+        // Composition is cancelled with no text, or ends as another panel opens
+        if text.is_empty() || app.panel != MainPanel::Typing {
+            return;
+        }
+
+        app.typer.push_str_from(&text, None);
+        drop(app);
+        play_cues();
     };
 
     let on_key_up = move |event: Event<KeyboardData>| {
-        let key = &event.key();
-        app.write().keyboard.update_for(&KeyState::new(key, false));
+        held.write().retain(|code| *code != event.code());
+        app.write().keyboard.update_for(&event.code(), false);
     };
 
+    // Keeps keys going to the typer on load and when it comes back, other panels taking the
+    // focus when opened so their controls are reached first
+    let mut root = use_signal(|| None::<std::rc::Rc<MountedData>>);
+    let mut panel_root = use_signal(|| None::<std::rc::Rc<MountedData>>);
+    let current_panel = use_memo(move || app.read().panel);
+    use_effect(move || {
+        let target = match current_panel() {
+            MainPanel::Typing => root(),
+            _ => panel_root(),
+        };
+        if let Some(target) = target {
+            spawn(async move {
+                let _ = target.set_focus(true).await;
+            });
+        }
+    });
+
     let panel = match app.read().panel {
         MainPanel::Typing => rsx! { TypingWindow {} },
         MainPanel::Info => rsx! { InfoWindow {} },
+        MainPanel::Race => rsx! { RaceWindow {} },
+        MainPanel::Settings => rsx! { SettingsWindow {} },
     };
 
+    let theme = app.read().settings.theme.style();
+
     rsx! {
         div {
 
@@ -171,14 +392,40 @@ fn App() -> Element {
         Stylesheet { href: asset!("./assets/tailwind.css") }
 
         div {
-            class: "h-screen flex bg-black roboto-mono text-white",
+            class: "h-screen flex bg-theme-background roboto-mono text-theme-text",
+            style: "{theme}",
             tabindex: "-1",
+            role: "application",
+            aria_label: "Hemi typing trainer",
             onkeydown: on_key_down,
             onkeypress: on_key_press,
             onkeyup: on_key_up,
+            onclick: move |_| {
+                if app.read().panel == MainPanel::Typing {
+                    if let Some(root) = root() {
+                        spawn(async move {
+                            let _ = root.set_focus(true).await;
+                        });
+                    }
+                }
+            },
+            textarea { class: "sr-only",
+                id: TYPING_INPUT,
+                aria_label: "typing input",
+                autocomplete: "off",
+                autocapitalize: "off",
+                spellcheck: "false",
+                onmounted: move |e| root.set(Some(e.data())),
+                oncompositionupdate: on_composition_update,
+                oncompositionend: on_composition_end,
+            }
             div { class: "h-screen w-screen overflow-hidden p-0 sm:p-4 flex flex-col mx-auto",
                 Header {},
-                {panel},
+                div { class: "flex flex-col grow focus:outline-none",
+                    tabindex: "-1",
+                    onmounted: move |e| panel_root.set(Some(e.data())),
+                    {panel}
+                }
                 Footer {}
             },
         }
@@ -196,16 +443,29 @@ fn Footer() -> Element {
         let panel = &mut app.write().panel;
 
         *panel = match panel {
-            MainPanel::Typing => MainPanel::Info,
             MainPanel::Info => MainPanel::Typing,
+            _ => MainPanel::Info,
+        };
+    };
+
+    let toggle_race = move |_| {
+        let panel = &mut app.write().panel;
+
+        *panel = match panel {
+            MainPanel::Race => MainPanel::Typing,
+            _ => MainPanel::Race,
         };
     };
 
+    let toggle_settings = move |_| perform(Action::Settings);
+
     rsx! {
-        div { class: "flex flex-row justify-between items-center m-5 text-sm text-neutral-400",
+        div { class: "flex flex-row justify-between items-center m-5 text-sm text-theme-muted",
             div { class: "flex flex-row gap-3",
                 a { class: "underline", href: "https://github.com/kualta/Hemi", "source" }
                 button { class: "underline", onclick: toggle_info, "about" }
+                button { class: "underline", onclick: toggle_race, "race" }
+                button { class: "underline", onclick: toggle_settings, "settings" }
             }
             div { " " }
             div { class: "flex flex-row gap-5",
@@ -216,68 +476,11 @@ fn Footer() -> Element {
 }
 
 fn Header() -> Element {
-    let mut app = use_context::<Signal<AppState>>();
-    let mut dictionary = use_context::<Signal<LayoutDictionary>>();
-    let layouts = use_context::<Signal<Layouts>>();
-
-    let flip_side = move |_| {
-        let mut app = app.write();
-        let dictionary = dictionary.read();
-
-        let newSide = match app.side {
-            TypingSide::Left => TypingSide::Right,
-            TypingSide::Right => TypingSide::Left,
-        };
-
-        app.side = newSide;
-        app.typer.drain();
-        app.refresh_keyboard(&dictionary);
-
-        match app.side {
-            TypingSide::Left => app.typer.generate_words(10, &dictionary.left),
-            TypingSide::Right => app.typer.generate_words(10, &dictionary.right),
-        }
-    };
-
-    let switch_layout = move |e: Event<FormData>| {
-        let mut app = app.write();
-        let mut dictionary = dictionary.write();
-        let layouts = layouts.read();
-        let current_layout = &mut app.layout;
-
-        *current_layout = match e.value().as_str() {
-            "colemak" => KeyboardLayout::Colemak,
-            "workman" => KeyboardLayout::Workman,
-            "qwerty" => KeyboardLayout::Qwerty,
-            "dvorak" => KeyboardLayout::Dvorak,
-            "custom" => KeyboardLayout::Custom,
-            _ => KeyboardLayout::Qwerty,
-        };
-
-        *dictionary = match current_layout {
-            KeyboardLayout::Qwerty => layouts.qwerty.clone(),
-            KeyboardLayout::Colemak => layouts.colemak.clone(),
-            _ => layouts.qwerty.clone(),
-        };
-
-        app.typer.drain();
-        app.refresh_keyboard(&dictionary);
-
-        match app.side {
-            TypingSide::Left => app.typer.generate_words(10, &dictionary.left),
-            TypingSide::Right => app.typer.generate_words(10, &dictionary.right),
-        }
-    };
-
-    let toggle_sound = move |_| {
-        let sound = &mut app.write().settings.sound_enabled;
-        *sound = !*sound;
-    };
+    let app = use_context::<Signal<AppState>>();
 
-    let toggle_keyboard = move |_| {
-        let keyboard = &mut app.write().settings.keyboard_enabled;
-        *keyboard = !*keyboard;
-    };
+    let flip_side = move |_| perform(Action::FlipSide);
+    let toggle_sound = move |_| perform(Action::Sound);
+    let toggle_keyboard = move |_| perform(Action::Keyboard);
 
     let sound_enabled = app.read().settings.sound_enabled;
     let keyboard_enabled = app.read().settings.keyboard_enabled;
@@ -286,8 +489,8 @@ fn Header() -> Element {
         div { class: "flex flex-row justify-between items-center m-5",
             div {
                 a { href: "#",
-                    h1 { class: "text-3xl md:text-4xl font-semibold tracking-tight leading-none text-white",
-                        mark { class: "px-2 mx-1 text-white bg-[#27272a] rounded dark:bg-[#27272a]",
+                    h1 { class: "text-3xl md:text-4xl font-semibold tracking-tight leading-none text-theme-text",
+                        mark { class: "px-2 mx-1 text-theme-text bg-theme-surface rounded",
                         "Hemi"
                      }
                         "Typer"
@@ -297,34 +500,31 @@ fn Header() -> Element {
             div { " " }
             div { class: "flex flex-row",
                 if keyboard_enabled {
-                    ToggleButton { onclick: toggle_keyboard, icon: "keyboard" }
+                    ToggleButton { onclick: toggle_keyboard, icon: "keyboard", label: "hide keyboard" }
                 } else {
-                    ToggleButton { onclick: toggle_keyboard, icon: "keyboard_off" }
+                    ToggleButton { onclick: toggle_keyboard, icon: "keyboard_off", label: "show keyboard" }
                 }
                 if sound_enabled {
-                    ToggleButton { onclick: toggle_sound, icon: "volume_up" }
+                    ToggleButton { onclick: toggle_sound, icon: "volume_up", label: "mute sound" }
                 } else {
-                    ToggleButton { onclick: toggle_sound, icon: "volume_off" }
-                }
-                ToggleButton { onclick: flip_side, icon: "cached" },
-                select { class: "mt-2 ml-5 bg-transparent dark:bg-transparent border border-white text-sm rounded-lg appearance-none text-center p-1 px-1.5 pb-1.5 items-center justify-center",
-                    name: "layout",
-                    id: "layout",
-                    onchange: switch_layout,
-                    option { value: "qwerty", "qwerty" }
-                    option { value: "colemak", "colemak" }
+                    ToggleButton { onclick: toggle_sound, icon: "volume_off", label: "unmute sound" }
                 }
+                ToggleButton { onclick: flip_side, icon: "cached", label: "flip typing side" },
             }
         }
     }
 }
 
 #[component]
-fn ToggleButton(onclick: EventHandler<MouseEvent>, icon: String) -> Element {
+fn ToggleButton(onclick: EventHandler<MouseEvent>, icon: String, label: String) -> Element {
     rsx! {
-        a {
+        button {
             class: "mt-3 ml-5",
-            href: "#",
+            r#type: "button",
+            aria_label: "{label}",
+            title: "{label}",
+            // Keeps focus on the app, so space and enter keep typing instead of pressing the button
+            onmousedown: |evt| evt.prevent_default(),
             onclick: move |evt| onclick.call(evt),
             {
                 match icon.as_str() {
@@ -344,22 +544,83 @@ fn TypingWindow() -> Element {
     let app = use_context::<Signal<AppState>>();
     let keyboard_enabled = app.read().settings.keyboard_enabled;
     let status_enabled = app.read().settings.status_enabled;
+    let race = use_context::<Signal<RaceState>>();
+    let in_race = matches!(
+        race.read().status,
+        RaceStatus::Racing | RaceStatus::Finished
+    );
+
+    // Re-renders the window while pacing, so the ghost keeps moving between keystrokes
+    let mut tick = use_signal(|| 0);
+    use_future(move || async move {
+        loop {
+            gloo_timers::future::TimeoutFuture::new(100).await;
+            if app.read().settings.pace != Pace::Off {
+                tick += 1;
+            }
+        }
+    });
+    tick.read();
 
     let app = app.read();
     let next = app.typer.next_word().unwrap_or(" ");
     let prev = app.typer.last_word();
     let current = app.typer.input();
+    let composition = &app.composition;
 
     let side_text_style = "pb-5 text-4xl font-bold text-transparent bg-clip-text
-                                bg-gradient-to-br from-zinc-50 to-zinc-200 basis-1/4 text-center";
-    let main_text_style = "pb-5 text-4xl font-bold text-white basis-1/4 text-left";
-
-    let typing_panel = rsx! {
-        div { class: "flex flex-row justify-center items-center content-center gap-5 p-10 my-auto h-32",
-            h2 { class: "{side_text_style}", "{prev}" }
-            h1 { class: "{main_text_style}", "{current}" }
-            h2 { class: "{side_text_style}", "{next}" }
+                                bg-gradient-to-br from-theme-text to-theme-accent basis-1/4 text-center";
+    let main_text_style = "pb-5 text-4xl font-bold text-theme-text basis-1/4 text-left";
+    let result_text_style = "pb-5 text-4xl font-bold text-theme-text basis-1/4 text-center";
+
+    // Ghost caret underlines the character it's at, while it's within the next word
+    let ghost = app
+        .pace_wpm()
+        .and_then(|wpm| app.typer.ghost(wpm))
+        .map(|ghost| ghost.floor() as isize)
+        .and_then(|ghost| usize::try_from(ghost).ok())
+        .filter(|&ghost| ghost < next.chars().count());
+    let next_word = match ghost {
+        Some(ghost) => {
+            let before: String = next.chars().take(ghost).collect();
+            let at: String = next.chars().skip(ghost).take(1).collect();
+            let after: String = next.chars().skip(ghost + 1).collect();
+            rsx! {
+                span { "{before}" }
+                span { class: "underline", "{at}" }
+                span { "{after}" }
+            }
         }
+        None => rsx! { "{next}" },
+    };
+
+    // Read out by screen readers whenever the word to type or the result changes
+    let announcement = match app.typer.result() {
+        Some(result) => format!(
+            "finished, {:.0} words per minute, {:.0} percent accuracy",
+            result.wpm(),
+            result.accuracy()
+        ),
+        None => format!("next word {next}"),
+    };
+
+    let typing_panel = match app.typer.result() {
+        Some(result) => rsx! {
+            div { class: "flex flex-row justify-center items-center content-center gap-5 p-10 my-auto h-32",
+                h1 { class: "{result_text_style}", "{result.wpm():.0} wpm" }
+                h2 { class: "{side_text_style}", "{result.accuracy():.0}%" }
+            }
+        },
+        None => rsx! {
+            div { class: "flex flex-row justify-center items-center content-center gap-5 p-10 my-auto h-32",
+                h2 { class: "{side_text_style}", "{prev}" }
+                h1 { class: "{main_text_style}",
+                    "{current}"
+                    span { class: "underline", "{composition}" }
+                }
+                h2 { class: "{side_text_style}", {next_word} }
+            }
+        },
     };
 
     let status_bar = if status_enabled {
@@ -368,6 +629,13 @@ fn TypingWindow() -> Element {
         rsx! { div {} }
     };
 
+    // Progress of the other players, while racing them and once finished
+    let race_bar = if in_race {
+        rsx! { RaceBar {} }
+    } else {
+        rsx! { div {} }
+    };
+
     let keyboard = if keyboard_enabled {
         rsx! { Keyboard {} }
     } else {
@@ -375,7 +643,25 @@ fn TypingWindow() -> Element {
     };
 
     rsx! {
-        div { class: "flex flex-col place-items-stretch h-screen gap-5 p-10", {status_bar}, {typing_panel}, {keyboard} }
+        div { class: "flex flex-col place-items-stretch h-screen gap-5 p-10",
+            {status_bar},
+            {race_bar},
+            {typing_panel},
+            div { class: "flex flex-row justify-center gap-5 text-sm text-theme-muted",
+                button { class: "underline",
+                    onmousedown: |evt| evt.prevent_default(),
+                    onclick: move |_| perform(Action::Restart),
+                    "restart"
+                }
+                button { class: "underline",
+                    onmousedown: |evt| evt.prevent_default(),
+                    onclick: move |_| perform(Action::NewTest),
+                    "new test"
+                }
+            }
+            p { class: "sr-only", aria_live: "polite", aria_atomic: "true", "{announcement}" }
+            {keyboard}
+        }
     }
 }
 
@@ -383,7 +669,7 @@ fn InfoWindow() -> Element {
     rsx!(
         div { class: "flex flex-col justify-center items-center content-center gap-5 p-10 my-auto",
             div { class: "w-96 m-auto text-center",
-                h1 { class: "text-xl tracking-tight text-white font-bold", "what" }
+                h1 { class: "text-xl tracking-tight text-theme-text font-bold", "what" }
                 p { class: "text-left",
                     "Hemi is a typing trainer that allows you to improve typing speed of your hands
                     separately, providing you with only half the keyboard per training session."
@@ -391,7 +677,7 @@ fn InfoWindow() -> Element {
             }
 
             div { class: "w-96 m-auto text-center mt-5",
-                h1 { class: "text-xl tracking-tight text-white font-bold", "why" }
+                h1 { class: "text-xl tracking-tight text-theme-text font-bold", "why" }
                 p { class: "text-left",
                     "I found that training raw typing speed this way yields
                     great results, but there wasn't any typing tutors that focused
@@ -400,7 +686,7 @@ fn InfoWindow() -> Element {
             }
 
             div { class: "w-96 m-auto text-center mt-5",
-                h1 { class: "text-xl tracking-tight text-white font-bold", "next" }
+                h1 { class: "text-xl tracking-tight text-theme-text font-bold", "next" }
                 p { class: "text-left",
                     "After you're done training here, I recommend you
                     to continue with a full-featured typing trainer like "
@@ -420,53 +706,134 @@ fn InfoWindow() -> Element {
 
 fn StatusBar() -> Element {
     let app = use_context::<Signal<AppState>>();
-    let streak = app.read().typer.streak();
+    let app = app.read();
+    let streak = app.typer.streak();
+    let side = app.side.name();
+    let alternating = app.typer.switch() != SideSwitch::Never;
+    let strict = app.typer.strict() != StrictMode::Off;
+    let accuracy = app.typer.accuracy();
+    let retries = app.typer.retries();
+    let wpm = app.typer.wpm();
+    let ghost = app
+        .pace_wpm()
+        .and_then(|pace| app.typer.ghost(pace))
+        .map(|ghost| (app.typer.input().chars().count() as f64 - ghost) / 5.0);
+    let wrong_hand = app.typer.stats(TypingSide::Left).wrong_hand()
+        + app.typer.stats(TypingSide::Right).wrong_hand();
+
+    let hands = [TypingSide::Left, TypingSide::Right].map(|side| {
+        let stats = app.typer.stats(side);
+        rsx! {
+            p { "{side.name()}: {stats.correct()}/{stats.correct() + stats.missed()} (best {stats.best_streak()}, {stats.accuracy():.0}%)" }
+        }
+    });
 
     rsx! {
-        div { class: "flex flex-row justify-between items-center m-5 text-sm text-neutral-400",
-            div { class: "flex flex-row gap-5", p { "streak: {streak}" } }
+        div { class: "flex flex-row justify-between items-center m-5 text-sm text-theme-muted",
+            div { class: "flex flex-row gap-5",
+                p { "streak: {streak}" }
+                p { "wpm: {wpm:.0}" }
+                p { "accuracy: {accuracy:.0}%" }
+                if let Some(ghost) = ghost {
+                    p { "ghost: {ghost:+.1}" }
+                }
+                if retries > 0 {
+                    p { "retries: {retries}" }
+                }
+                if strict {
+                    p { "wrong hand: {wrong_hand}" }
+                }
+            }
+            if alternating {
+                div { class: "flex flex-row gap-5",
+                    p { class: "text-theme-text", "hand: {side}" }
+                    {hands.into_iter()}
+                }
+            }
         }
     }
 }
 
+/// Size of a letter key on the displayed keyboard, in rem
+const KEY_UNIT: f32 = 4.0;
+
+/// Space left between neighbouring keys, in rem
+const KEY_GAP: f32 = 0.5;
+
+/// Color marking keys of `finger`, mirrored between the hands
+fn finger_color(finger: Finger) -> &'static str {
+    match finger {
+        Finger::LeftPinky | Finger::RightPinky => "#f87171",
+        Finger::LeftRing | Finger::RightRing => "#fb923c",
+        Finger::LeftMiddle | Finger::RightMiddle => "#facc15",
+        Finger::LeftIndex | Finger::RightIndex => "#4ade80",
+        Finger::LeftThumb | Finger::RightThumb => "#60a5fa",
+    }
+}
+
 fn Keyboard() -> Element {
     let app = use_context::<Signal<AppState>>();
-    let keyboard = &app.read().keyboard;
-
-    let button_active = "w-16 h-14 text-white border-2 border-zinc-300
-    focus:outline-none focus:ring-4 focus:ring-zinc-200
-     font-medium rounded-lg text-xl px-5 py-2.5 mr-2 mb-2 bg-[#27272a]
-      text-white border-zinc-600";
-    let button_inactive = "w-16 h-14 text-zinc-400 focus:outline-none focus:ring-4
-     focus:ring-zinc-700 font-medium rounded-lg text-xl px-5 py-2.5 mr-2 mb-2 bg-[#27272a]
-      text-white border-zinc-600";
+    let app = app.read();
+    let keyboard = &app.keyboard;
+    let finger_colors = app.settings.finger_colors;
+    let next_key = app.next_key().filter(|_| app.settings.key_hint);
+    let next_finger = next_key
+        .and_then(|code| keyboard.get(&code))
+        .and_then(|key| key.finger());
+
+    let button_active = "absolute text-theme-text border-2 border-theme-text
+    focus:outline-none focus:ring-4 focus:ring-theme-accent
+     font-medium rounded-lg text-xl px-1 bg-theme-surface
+      text-theme-text border-theme-surface";
+    let button_inactive = "absolute text-theme-muted focus:outline-none focus:ring-4
+     focus:ring-theme-muted font-medium rounded-lg text-xl px-1 bg-theme-surface
+      text-theme-text border-theme-surface";
+
+    let (width, height) = keyboard.size();
+    let size = format!(
+        "width: {}rem; height: {}rem;",
+        width * KEY_UNIT - KEY_GAP,
+        height * KEY_UNIT - KEY_GAP
+    );
 
     let keyboard = rsx! {
-        {keyboard.keys().iter().enumerate().map(|(i, row)| {
-            let row_indents = ["ml-10", "ml-20", "ml-[7.5rem]"];
-            rsx! {
-                span { class: row_indents[i] }
-                span {
-                    {row.iter().map(|key| {
-                        let button_style = if key.enabled() { button_active } else { button_inactive };
-                        rsx! {
-                            button {
-                                class: "{button_style}",
-                                 "type": "button",
-                                  "{key.key()}"
-                                 }
-                        }
-                    })}
+        div { class: "relative m-auto", style: "{size}",
+            {keyboard.keys().iter().flatten().map(|key| {
+                let button_style = if key.enabled() { button_active } else { button_inactive };
+                let hint = if next_key == Some(key.code()) { "ring-4 ring-theme-accent" } else { "" };
+                let dimmed = if key.active() { "" } else { "opacity-40" };
+                let shape = key.shape();
+                let position = format!(
+                    "left: {}rem; top: {}rem; width: {}rem; height: {}rem;",
+                    shape.x * KEY_UNIT,
+                    shape.y * KEY_UNIT,
+                    shape.width * KEY_UNIT - KEY_GAP,
+                    KEY_UNIT - KEY_GAP
+                );
+                let color = match key.finger() {
+                    Some(finger) if finger_colors => format!("border-bottom: 4px solid {};", finger_color(finger)),
+                    _ => String::new(),
+                };
+                rsx! {
+                    button {
+                        class: "{button_style} {hint} {dimmed}",
+                        style: "{position} {color}",
+                        "type": "button",
+                        "{key.legend()}"
+                    }
                 }
-                br { }
-            }
-        })}
+            })}
+        }
+        if let Some(finger) = next_finger {
+            p { class: "text-sm text-theme-muted mt-5", "next: {finger.name()}" }
+        }
     };
 
     rsx! {
         div {
             class: "content-center text-center overflow-visible w-max m-auto gap-5",
             id: "keyboard",
+            aria_hidden: "true",
             {keyboard}
         }
     }
@@ -478,7 +845,8 @@ mod icons {
     pub(super) fn keyboard() -> Element {
         rsx! {
             svg { class: "mr-3",
-                fill: "#FFFFFF",
+                "aria-hidden": "true",
+                fill: "currentColor",
                 xmlns: "http://www.w3.org/2000/svg",
                 view_box: "0 -960 960 960",
                 width: "24",
@@ -504,7 +872,8 @@ mod icons {
     pub(super) fn keyboard_off() -> Element {
         rsx! {
             svg { class: "mr-3",
-                fill: "#FFFFFF",
+                "aria-hidden": "true",
+                fill: "currentColor",
                 xmlns: "http://www.w3.org/2000/svg",
                 view_box: "0 -960 960 960",
                 width: "24",
@@ -530,7 +899,8 @@ mod icons {
     pub(super) fn volume_up() -> Element {
         rsx! {
             svg { class: "mr-3",
-                fill: "#FFFFFF",
+                "aria-hidden": "true",
+                fill: "currentColor",
                 xmlns: "http://www.w3.org/2000/svg",
                 view_box: "0 -960 960 960",
                 width: "24",
@@ -556,7 +926,8 @@ mod icons {
     pub(super) fn volume_off() -> Element {
         rsx! {
             svg { class: "mr-3",
-                fill: "#FFFFFF",
+                "aria-hidden": "true",
+                fill: "currentColor",
                 xmlns: "http://www.w3.org/2000/svg",
                 view_box: "0 -960 960 960",
                 width: "24",
@@ -582,7 +953,8 @@ mod icons {
     pub(super) fn cached() -> Element {
         rsx! {
             svg { class: "mr-3",
-                fill: "#FFFFFF",
+                "aria-hidden": "true",
+                fill: "currentColor",
                 xmlns: "http://www.w3.org/2000/svg",
                 view_box: "0 -960 960 960",
                 width: "24",
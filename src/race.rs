@@ -0,0 +1,298 @@
+use crate::AppState;
+use dioxus::prelude::*;
+use futures_util::future::{select, Either};
+use futures_util::{SinkExt, StreamExt};
+use gloo_net::websocket::{futures::WebSocket, Message};
+use hemi_core::protocol::{ClientMessage, ServerMessage, RACE_PATH, RACE_WORDS};
+use hemi_core::words::{LayoutDictionary, TypingData, TypingSide};
+
+#[derive(Clone, Copy, PartialEq, Default)]
+pub(crate) enum RaceStatus {
+    #[default]
+    Offline,
+    Connecting,
+    Lobby,
+    Racing,
+    Finished,
+}
+
+/// Progress of a player in the room
+#[derive(Clone)]
+pub(crate) struct Racer {
+    pub(crate) id: u32,
+    pub(crate) name: String,
+    pub(crate) words: usize,
+    pub(crate) wpm: f64,
+    pub(crate) place: Option<usize>,
+}
+
+/// State of the race room the player is in
+#[derive(Clone, Default)]
+pub(crate) struct RaceState {
+    pub(crate) status: RaceStatus,
+    pub(crate) id: Option<u32>,
+    pub(crate) side: TypingSide,
+    pub(crate) racers: Vec<Racer>,
+    pub(crate) error: Option<String>,
+}
+
+impl RaceState {
+    pub(crate) fn is_racing(&self) -> bool {
+        self.status == RaceStatus::Racing
+    }
+}
+
+pub(crate) enum RaceCommand {
+    Join { room: String, name: String },
+    Send(ClientMessage),
+    Leave,
+}
+
+/// Message reporting progress of the session to the room
+pub(crate) fn report(typer: &TypingData) -> ClientMessage {
+    match typer.result() {
+        Some(result) => ClientMessage::Finish {
+            wpm: result.wpm(),
+            accuracy: result.accuracy(),
+        },
+        None => ClientMessage::Progress {
+            words: typer.submitted(),
+            wpm: typer.wpm(),
+        },
+    }
+}
+
+/// WebSocket URL of the race server, hosted behind the same origin as the app
+fn server_url() -> Option<String> {
+    let location = web_sys::window()?.location();
+    let scheme = match location.protocol().ok()?.as_str() {
+        "https:" => "wss",
+        _ => "ws",
+    };
+
+    Some(format!("{scheme}://{}{RACE_PATH}", location.host().ok()?))
+}
+
+/// Connects to the race server on [`RaceCommand::Join`] and keeps `race` in sync with the room
+pub(crate) async fn race_client(
+    mut commands: UnboundedReceiver<RaceCommand>,
+    mut race: Signal<RaceState>,
+    mut app: Signal<AppState>,
+    dictionary: Signal<LayoutDictionary>,
+) {
+    while let Some(command) = commands.next().await {
+        let RaceCommand::Join { room, name } = command else {
+            continue;
+        };
+
+        *race.write() = RaceState {
+            status: RaceStatus::Connecting,
+            ..Default::default()
+        };
+
+        let socket = server_url().and_then(|url| WebSocket::open(&url).ok());
+        let Some(socket) = socket else {
+            race.write().status = RaceStatus::Offline;
+            race.write().error = Some("failed to connect".to_owned());
+            continue;
+        };
+
+        let (mut sink, mut stream) = socket.split();
+        let join = ClientMessage::Join {
+            room,
+            name,
+            layout: app.read().settings.layout.name().to_owned(),
+            side: app.read().side,
+        };
+
+        let mut outgoing = Some(join);
+        loop {
+            if let Some(message) = outgoing.take() {
+                let text = serde_json::to_string(&message).expect("Messages are serializable");
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+
+            match select(commands.next(), stream.next()).await {
+                Either::Left((Some(RaceCommand::Send(message)), _)) => outgoing = Some(message),
+                Either::Left((Some(RaceCommand::Join { .. }), _)) => (),
+                Either::Left((Some(RaceCommand::Leave) | None, _)) => break,
+                Either::Right((Some(Ok(Message::Text(text))), _)) => {
+                    match serde_json::from_str::<ServerMessage>(&text) {
+                        Ok(message) => {
+                            if !receive(message, &mut race, &mut app, &dictionary) {
+                                break;
+                            }
+                        }
+                        Err(err) => log::warn!("unexpected race message: {err}"),
+                    }
+                }
+                Either::Right((Some(Ok(Message::Bytes(_))), _)) => (),
+                Either::Right((Some(Err(_)) | None, _)) => {
+                    race.write().error = Some("connection lost".to_owned());
+                    break;
+                }
+            }
+        }
+
+        race.write().status = RaceStatus::Offline;
+        // Sessions go back to the settings of the player when leaving mid race
+        app.write().typer.release_words();
+    }
+}
+
+/// Applies `message` from the room to `race`, false when the room can't be raced from here
+fn receive(
+    message: ServerMessage,
+    race: &mut Signal<RaceState>,
+    app: &mut Signal<AppState>,
+    dictionary: &Signal<LayoutDictionary>,
+) -> bool {
+    let mut race = race.write();
+
+    match message {
+        ServerMessage::Joined { id, layout, side } => {
+            // Words of the room are only typeable on its layout
+            if layout != app.read().settings.layout.name() {
+                race.error = Some(format!("room races on {layout}, switch to it to join"));
+                return false;
+            }
+
+            race.status = RaceStatus::Lobby;
+            race.id = Some(id);
+            race.side = side;
+        }
+        ServerMessage::Players { players } => {
+            let racers = players
+                .into_iter()
+                .map(|player| {
+                    let racer = race.racers.iter().find(|racer| racer.id == player.id);
+                    Racer {
+                        id: player.id,
+                        name: player.name,
+                        words: racer.map_or(0, |racer| racer.words),
+                        wpm: racer.map_or(0.0, |racer| racer.wpm),
+                        place: racer.and_then(|racer| racer.place),
+                    }
+                })
+                .collect();
+            race.racers = racers;
+        }
+        ServerMessage::Start { words, .. } => {
+            race.status = RaceStatus::Racing;
+            race.error = None;
+            for racer in race.racers.iter_mut() {
+                racer.words = 0;
+                racer.wpm = 0.0;
+                racer.place = None;
+            }
+
+            let mut app = app.write();
+            app.typer.set_words(words, race.side);
+            app.sync_side(&dictionary.read());
+            app.panel = crate::MainPanel::Typing;
+        }
+        ServerMessage::Progress { id, words, wpm } => {
+            if let Some(racer) = race.racers.iter_mut().find(|racer| racer.id == id) {
+                racer.words = words;
+                racer.wpm = wpm;
+            }
+        }
+        ServerMessage::Finished { id, wpm, place, .. } => {
+            if let Some(racer) = race.racers.iter_mut().find(|racer| racer.id == id) {
+                racer.words = RACE_WORDS;
+                racer.wpm = wpm;
+                racer.place = Some(place);
+            }
+            if race.id == Some(id) {
+                race.status = RaceStatus::Finished;
+            }
+        }
+        ServerMessage::Error { message } => race.error = Some(message),
+    }
+
+    true
+}
+
+pub(crate) fn RaceWindow() -> Element {
+    let race = use_context::<Signal<RaceState>>();
+    let client = use_coroutine_handle::<RaceCommand>();
+    let mut room = use_signal(|| "hemi".to_owned());
+    let mut name = use_signal(|| "anonymous".to_owned());
+
+    let input_style = "mt-2 bg-transparent border border-theme-text text-sm rounded-lg p-1 px-1.5";
+    let status = race.read().status;
+    let error = race.read().error.clone();
+
+    let controls = match status {
+        RaceStatus::Offline => rsx! {
+            input { class: "{input_style}",
+                aria_label: "room",
+                value: "{room}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                oninput: move |e| room.set(e.value()),
+            }
+            input { class: "{input_style}",
+                aria_label: "name",
+                value: "{name}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                oninput: move |e| name.set(e.value()),
+            }
+            button { class: "underline mt-2",
+                onclick: move |_| client.send(RaceCommand::Join { room: room(), name: name() }),
+                "join"
+            }
+        },
+        RaceStatus::Connecting => rsx! { p { "connecting..." } },
+        _ => rsx! {
+            button { class: "underline",
+                onclick: move |_| client.send(RaceCommand::Send(ClientMessage::Start)),
+                "start"
+            }
+            button { class: "underline",
+                onclick: move |_| client.send(RaceCommand::Leave),
+                "leave"
+            }
+        },
+    };
+
+    rsx! {
+        div { class: "flex flex-col justify-center items-center content-center gap-5 p-10 my-auto",
+            div { class: "w-96 m-auto text-center",
+                h1 { class: "text-xl tracking-tight text-theme-text font-bold", "race" }
+                p { class: "text-left",
+                    "Race your team on the same words, the first player to join a room
+                    picks its layout and side."
+                }
+            }
+            div { class: "flex flex-row gap-5", {controls} }
+            if let Some(error) = error {
+                p { class: "text-theme-muted", "{error}" }
+            }
+            RaceBar {}
+        }
+    }
+}
+
+/// Progress of every player in the room
+pub(crate) fn RaceBar() -> Element {
+    let race = use_context::<Signal<RaceState>>();
+    let race = race.read();
+
+    rsx! {
+        div { class: "flex flex-col gap-3 text-sm text-theme-muted",
+            {race.racers.iter().map(|racer| {
+                let place = racer.place.map(|place| format!("#{place}")).unwrap_or_default();
+                let you = if race.id == Some(racer.id) { " (you)" } else { "" };
+                rsx! {
+                    p { key: "{racer.id}",
+                        "{racer.name}{you}: {racer.words}/{RACE_WORDS} words, {racer.wpm:.0} wpm {place}"
+                    }
+                }
+            })}
+        }
+    }
+}
@@ -0,0 +1,635 @@
+use crate::race::RaceState;
+use crate::shortcuts::{Action, Shortcuts};
+use crate::sound::{Cues, StreakCue, BUNDLED_PACKS};
+use crate::storage;
+use crate::theme::{Theme, ThemePicker};
+use crate::words::RemoteLayouts;
+use crate::AppState;
+use dioxus::html::input_data::keyboard_types::Code;
+use dioxus::prelude::*;
+use hemi_core::geometry::Geometry;
+use hemi_core::import::{self, LayoutKeys};
+use hemi_core::keys::ThumbKey;
+use hemi_core::words::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum KeyboardLayout {
+    #[default]
+    Qwerty,
+    Dvorak,
+    Colemak,
+    Workman,
+    Jcuken,
+    Custom,
+}
+
+impl KeyboardLayout {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "qwerty",
+            KeyboardLayout::Dvorak => "dvorak",
+            KeyboardLayout::Colemak => "colemak",
+            KeyboardLayout::Workman => "workman",
+            KeyboardLayout::Jcuken => "jcuken",
+            KeyboardLayout::Custom => "custom",
+        }
+    }
+
+    /// Layout following this one in the settings, wrapping around
+    pub(crate) fn next(&self) -> Self {
+        let layouts = KeyboardLayout::CHOICES;
+        let current = layouts.iter().position(|(layout, _)| layout == self);
+        let next = current.map_or(0, |current| (current + 1) % layouts.len());
+
+        layouts[next].0
+    }
+}
+
+/// Speed of the ghost caret racing the input
+#[derive(Clone, Debug, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Pace {
+    #[default]
+    Off,
+    Wpm(u32),
+    /// Personal best for the current layout and hand
+    Best,
+}
+
+/// Where the word lists of the layouts come from
+#[derive(Clone, Debug, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WordSource {
+    /// Latest lists from the repository, falling back to the bundled ones while offline
+    #[default]
+    Remote,
+    Bundled,
+}
+
+/// Word lists of every [`WordSource`]
+#[derive(Clone)]
+pub(crate) struct WordSources {
+    bundled: Layouts,
+    remote: Option<Layouts>,
+}
+
+impl Default for WordSources {
+    fn default() -> Self {
+        Self {
+            bundled: Layouts::bundled(),
+            remote: None,
+        }
+    }
+}
+
+impl WordSources {
+    pub(crate) fn get(&self, source: WordSource) -> &Layouts {
+        match (source, &self.remote) {
+            (WordSource::Remote, Some(remote)) => remote,
+            _ => &self.bundled,
+        }
+    }
+
+    pub(crate) fn set_remote(&mut self, layouts: RemoteLayouts) {
+        self.remote = Some(layouts.or(&self.bundled));
+    }
+}
+
+/// Thumb key submitting a word on each half
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SubmitKeys {
+    pub(crate) left: ThumbKey,
+    pub(crate) right: ThumbKey,
+}
+
+impl Default for SubmitKeys {
+    fn default() -> Self {
+        SubmitKeys {
+            left: ThumbKey::Space,
+            right: ThumbKey::Space,
+        }
+    }
+}
+
+impl SubmitKeys {
+    /// Physical key submitting words typed by the hand of `side`
+    pub(crate) fn code(&self, side: TypingSide) -> Code {
+        match side {
+            TypingSide::Left => self.left.code(side),
+            TypingSide::Right => self.right.code(side),
+        }
+    }
+
+    /// Whether `code` submits words typed by the hand of `side`, Enter submitting on either
+    pub(crate) fn submits(&self, side: TypingSide, code: Code) -> bool {
+        code == self.code(side) || code == Code::Enter
+    }
+}
+
+/// Every user preference, persisted in local storage
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AppSettings {
+    pub(crate) sound_enabled: bool,
+    /// Name of a bundled sound pack, or URL of a sound pack manifest
+    pub(crate) sound_pack: String,
+    /// Volume of key sounds, from 0 to 1
+    pub(crate) volume: f32,
+    pub(crate) cues: Cues,
+    pub(crate) status_enabled: bool,
+    pub(crate) keyboard_enabled: bool,
+    /// Colors keys by the finger pressing them
+    pub(crate) finger_colors: bool,
+    /// Highlights the key and finger of the next character to type
+    pub(crate) key_hint: bool,
+    pub(crate) emulate_layout: bool,
+    /// Physical arrangement of the keys on the displayed keyboard
+    pub(crate) geometry: Geometry,
+    pub(crate) submit: SubmitKeys,
+    pub(crate) pace: Pace,
+    pub(crate) layout: KeyboardLayout,
+    /// Keys of the custom layout, imported from an XKB or KLC file
+    pub(crate) custom_layout: Option<LayoutKeys>,
+    pub(crate) side: TypingSide,
+    pub(crate) switch: SideSwitch,
+    pub(crate) strict: StrictMode,
+    pub(crate) policy: InputPolicy,
+    pub(crate) retry: RetryMode,
+    pub(crate) length: TestLength,
+    pub(crate) buffer: BufferSize,
+    pub(crate) source: WordSource,
+    pub(crate) theme: Theme,
+    pub(crate) shortcuts: Shortcuts,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            sound_enabled: true,
+            sound_pack: BUNDLED_PACKS[0].to_owned(),
+            volume: 0.5,
+            cues: Cues::default(),
+            status_enabled: true,
+            keyboard_enabled: true,
+            finger_colors: false,
+            key_hint: false,
+            emulate_layout: false,
+            geometry: Geometry::default(),
+            submit: SubmitKeys::default(),
+            pace: Pace::default(),
+            layout: KeyboardLayout::default(),
+            custom_layout: None,
+            side: TypingSide::default(),
+            switch: SideSwitch::default(),
+            strict: StrictMode::default(),
+            policy: InputPolicy::default(),
+            retry: RetryMode::default(),
+            length: TestLength::default(),
+            buffer: BufferSize::default(),
+            source: WordSource::default(),
+            theme: Theme::default(),
+            shortcuts: Shortcuts::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    const KEY: &'static str = "hemi.settings";
+
+    pub(crate) fn load() -> Self {
+        let mut settings: Self = storage::load(Self::KEY).unwrap_or_default();
+
+        // Stored themes are edited by hand as well
+        if settings.theme.validate().is_err() {
+            settings.theme = Theme::default();
+        }
+
+        settings
+    }
+
+    pub(crate) fn save(&self) {
+        storage::save(Self::KEY, self);
+    }
+
+    /// Dictionary of the selected layout and word source, qwerty if the layout has none.
+    ///
+    /// Custom layouts take the words of every bundled layout that fit on their halves.
+    pub(crate) fn dictionary(&self, sources: &WordSources) -> LayoutDictionary {
+        let layouts = sources.get(self.source);
+
+        match (self.layout, &self.custom_layout) {
+            (KeyboardLayout::Custom, Some(keys)) => keys.dictionary(layouts.words()),
+            _ => layouts
+                .get(self.layout.name())
+                .unwrap_or(&layouts.qwerty)
+                .clone(),
+        }
+    }
+
+    /// Whether switching from `other` requires new words to be generated
+    fn regenerates(&self, other: &AppSettings) -> bool {
+        self.layout != other.layout
+            || self.custom_layout != other.custom_layout
+            || self.source != other.source
+            || self.side != other.side
+            || self.switch != other.switch
+    }
+
+    /// Whether switching from `other` changes the words or the session, which races fix
+    fn changes_session(&self, other: &AppSettings) -> bool {
+        self.regenerates(other) || self.length != other.length || self.retry != other.retry
+    }
+}
+
+impl AppState {
+    /// Applies `settings` to the session and saves them, regenerating words only if needed
+    pub(crate) fn configure(
+        &mut self,
+        settings: AppSettings,
+        sources: &WordSources,
+        dictionary: &mut LayoutDictionary,
+    ) {
+        let previous = std::mem::replace(&mut self.settings, settings);
+
+        self.typer.set_strict(self.settings.strict);
+        self.typer.set_policy(self.settings.policy);
+        self.typer.set_retry(self.settings.retry);
+        self.typer.set_buffer_size(self.settings.buffer);
+
+        if previous.length != self.settings.length {
+            self.typer.set_length(self.settings.length);
+        }
+
+        if self.settings.regenerates(&previous) {
+            self.reload(sources, dictionary);
+        } else if previous.geometry != self.settings.geometry {
+            self.refresh_keyboard(dictionary);
+        }
+
+        self.settings.save();
+    }
+
+    /// Starts a new session on words from the selected layout and word source
+    pub(crate) fn reload(&mut self, sources: &WordSources, dictionary: &mut LayoutDictionary) {
+        *dictionary = self.settings.dictionary(sources);
+
+        self.side = self.settings.side;
+        self.typer.set_switch(self.settings.switch);
+        self.typer.set_side(self.side);
+        self.typer.reset_session();
+        self.refresh_keyboard(dictionary);
+        self.typer.refill(dictionary);
+    }
+}
+
+/// Applies `change` to the settings of the app
+pub(crate) fn update_settings(change: impl FnOnce(&mut AppSettings)) {
+    let mut app = consume_context::<Signal<AppState>>();
+    let mut dictionary = consume_context::<Signal<LayoutDictionary>>();
+    let sources = consume_context::<Signal<WordSources>>();
+
+    let mut settings = app.read().settings.clone();
+    change(&mut settings);
+
+    // Races are typed on the words, side and length given by the server
+    let racing = consume_context::<Signal<RaceState>>().read().is_racing();
+    if racing && settings.changes_session(&app.read().settings) {
+        return;
+    }
+
+    app.write()
+        .configure(settings, &sources.read(), &mut dictionary.write());
+}
+
+/// Setting picked from a fixed list of labelled values
+trait Choice: Copy + PartialEq + 'static {
+    const CHOICES: &'static [(Self, &'static str)];
+}
+
+impl Choice for KeyboardLayout {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (KeyboardLayout::Qwerty, "qwerty"),
+        (KeyboardLayout::Colemak, "colemak"),
+        (KeyboardLayout::Dvorak, "dvorak"),
+        (KeyboardLayout::Workman, "workman"),
+        (KeyboardLayout::Jcuken, "йцукен"),
+        (KeyboardLayout::Custom, "custom"),
+    ];
+}
+
+impl Choice for Geometry {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (Geometry::Ansi, "ansi"),
+        (Geometry::Iso, "iso"),
+        (Geometry::Ortholinear, "ortholinear"),
+        (Geometry::Split, "split"),
+    ];
+}
+
+impl Choice for ThumbKey {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (ThumbKey::Space, "space"),
+        (ThumbKey::Enter, "enter"),
+        (ThumbKey::Alt, "alt"),
+        (ThumbKey::Meta, "meta"),
+    ];
+}
+
+impl Choice for TypingSide {
+    const CHOICES: &'static [(Self, &'static str)] =
+        &[(TypingSide::Left, "left"), (TypingSide::Right, "right")];
+}
+
+impl Choice for SideSwitch {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (SideSwitch::Never, "single"),
+        (SideSwitch::Words(1), "alternate"),
+        (SideSwitch::Words(5), "every 5"),
+        (SideSwitch::Sentence, "sentences"),
+    ];
+}
+
+impl Choice for StrictMode {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (StrictMode::Off, "any hand"),
+        (StrictMode::Flag, "flag hand"),
+        (StrictMode::Block, "block hand"),
+    ];
+}
+
+impl Choice for InputPolicy {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (InputPolicy::Free, "free"),
+        (InputPolicy::StopOnError, "stop on error"),
+        (InputPolicy::NoCorrection, "no backspace"),
+    ];
+}
+
+impl Choice for RetryMode {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (RetryMode::Off, "no retry"),
+        (RetryMode::Later, "retry later"),
+        (RetryMode::Immediate, "retry now"),
+    ];
+}
+
+impl Choice for TestLength {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (TestLength::Endless, "endless"),
+        (TestLength::Words(25), "25 words"),
+        (TestLength::Words(50), "50 words"),
+        (TestLength::Words(100), "100 words"),
+    ];
+}
+
+impl Choice for Pace {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (Pace::Off, "no pace"),
+        (Pace::Wpm(40), "pace 40"),
+        (Pace::Wpm(60), "pace 60"),
+        (Pace::Wpm(80), "pace 80"),
+        (Pace::Best, "pace best"),
+    ];
+}
+
+impl Choice for WordSource {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (WordSource::Remote, "latest"),
+        (WordSource::Bundled, "bundled"),
+    ];
+}
+
+impl Choice for StreakCue {
+    const CHOICES: &'static [(Self, &'static str)] = &[
+        (StreakCue::Off, "off"),
+        (StreakCue::Every(10), "every 10"),
+        (StreakCue::Every(25), "every 25"),
+        (StreakCue::Every(50), "every 50"),
+    ];
+}
+
+impl Choice for bool {
+    const CHOICES: &'static [(Self, &'static str)] = &[(true, "on"), (false, "off")];
+}
+
+pub(crate) const SELECT_STYLE: &str = "bg-transparent dark:bg-transparent border border-theme-text text-sm rounded-lg appearance-none text-center p-1 px-1.5 pb-1.5";
+
+fn select<T: Choice>(label: &str, current: T, apply: fn(&mut AppSettings, T)) -> Element {
+    let choose = move |e: Event<FormData>| {
+        let value = e.value();
+        if let Some(&(choice, _)) = T::CHOICES.iter().find(|(_, name)| *name == value) {
+            update_settings(|settings| apply(settings, choice));
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "{label}" }
+            select { class: SELECT_STYLE,
+                onchange: choose,
+                for (choice, name) in T::CHOICES {
+                    option { value: *name, selected: *choice == current, "{name}" }
+                }
+            }
+        }
+    }
+}
+
+fn number(label: &str, current: usize, apply: fn(&mut AppSettings, usize)) -> Element {
+    let change = move |e: Event<FormData>| {
+        if let Ok(value @ 1..) = e.value().parse::<usize>() {
+            update_settings(|settings| apply(settings, value));
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "{label}" }
+            input { class: "{SELECT_STYLE} w-16",
+                r#type: "number",
+                min: "1",
+                value: "{current}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                onchange: change,
+            }
+        }
+    }
+}
+
+fn shortcut(action: Action, current: String) -> Element {
+    let change = move |e: Event<FormData>| {
+        if let Ok(shortcut) = e.value().parse() {
+            update_settings(|settings| *settings.shortcuts.get_mut(action) = shortcut);
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "{action.name()}" }
+            input { class: "{SELECT_STYLE} w-32",
+                value: "{current}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                onchange: change,
+            }
+        }
+    }
+}
+
+fn sound_pack(current: String) -> Element {
+    let change = move |e: Event<FormData>| {
+        let pack = e.value().trim().to_owned();
+        if !pack.is_empty() {
+            update_settings(|settings| settings.sound_pack = pack);
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "sound pack" }
+            input { class: "{SELECT_STYLE} w-32",
+                list: "sound-packs",
+                placeholder: "name or manifest url",
+                value: "{current}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                onchange: change,
+            }
+            datalist { id: "sound-packs",
+                for pack in BUNDLED_PACKS {
+                    option { value: pack }
+                }
+            }
+        }
+    }
+}
+
+fn volume(current: f32) -> Element {
+    let change = move |e: Event<FormData>| {
+        if let Ok(percent) = e.value().parse::<f32>() {
+            update_settings(|settings| settings.volume = (percent / 100.0).clamp(0.0, 1.0));
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "volume" }
+            input { class: "w-32",
+                r#type: "range",
+                min: "0",
+                max: "100",
+                value: "{current * 100.0:.0}",
+                onkeydown: |e| e.stop_propagation(),
+                onkeypress: |e| e.stop_propagation(),
+                onchange: change,
+            }
+        }
+    }
+}
+
+/// Imports the custom layout from an XKB symbols or KLC file, and selects it
+fn LayoutImport() -> Element {
+    let mut error = use_signal(|| None::<String>);
+
+    let import = move |e: Event<FormData>| async move {
+        let Some(files) = e.files() else {
+            return;
+        };
+        let Some(name) = files.files().into_iter().next() else {
+            return;
+        };
+        let Some(bytes) = files.read_file(&name).await else {
+            error.set(Some(format!("failed to read {name}")));
+            return;
+        };
+
+        match import::import(&import::decode(&bytes)) {
+            Ok(keys) => {
+                error.set(None);
+                update_settings(|settings| {
+                    settings.custom_layout = Some(keys);
+                    settings.layout = KeyboardLayout::Custom;
+                });
+            }
+            Err(err) => error.set(Some(format!("invalid layout: {err}"))),
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "import layout" }
+            input { class: "text-xs w-32",
+                r#type: "file",
+                accept: ".klc,.xkb,.txt,text/plain",
+                onchange: import,
+            }
+        }
+        if let Some(error) = error() {
+            p { class: "text-theme-muted", "{error}" }
+        }
+    }
+}
+
+#[component]
+fn Group(title: String, children: Element) -> Element {
+    rsx! {
+        div { class: "flex flex-col gap-3 w-64",
+            h1 { class: "text-xl tracking-tight text-theme-text font-bold", "{title}" }
+            {children}
+        }
+    }
+}
+
+pub(crate) fn SettingsWindow() -> Element {
+    let app = use_context::<Signal<AppState>>();
+    let settings = app.read().settings.clone();
+
+    rsx! {
+        div { class: "flex flex-row flex-wrap justify-center gap-10 p-10 my-auto text-sm",
+            Group { title: "display",
+                {select("status bar", settings.status_enabled, |s, v| s.status_enabled = v)}
+                {select("keyboard", settings.keyboard_enabled, |s, v| s.keyboard_enabled = v)}
+                {select("finger colors", settings.finger_colors, |s, v| s.finger_colors = v)}
+                {select("next key", settings.key_hint, |s, v| s.key_hint = v)}
+                {select("sound", settings.sound_enabled, |s, v| s.sound_enabled = v)}
+                {sound_pack(settings.sound_pack.clone())}
+                {volume(settings.volume)}
+                {select("error cue", settings.cues.error, |s, v| s.cues.error = v)}
+                {select("streak cue", settings.cues.streak, |s, v| s.cues.streak = v)}
+                {select("finish cue", settings.cues.finish, |s, v| s.cues.finish = v)}
+                {select("ghost", settings.pace, |s, v| s.pace = v)}
+                ThemePicker {}
+            }
+            Group { title: "layout",
+                {select("layout", settings.layout, |s, v| s.layout = v)}
+                LayoutImport {}
+                {select("emulate layout", settings.emulate_layout, |s, v| s.emulate_layout = v)}
+                {select("geometry", settings.geometry, |s, v| s.geometry = v)}
+                {select("side", settings.side, |s, v| s.side = v)}
+                {select("hands", settings.switch, |s, v| s.switch = v)}
+                {select("left submit", settings.submit.left, |s, v| s.submit.left = v)}
+                {select("right submit", settings.submit.right, |s, v| s.submit.right = v)}
+            }
+            Group { title: "test",
+                {select("length", settings.length, |s, v| s.length = v)}
+                {select("strict", settings.strict, |s, v| s.strict = v)}
+                {select("corrections", settings.policy, |s, v| s.policy = v)}
+                {select("missed words", settings.retry, |s, v| s.retry = v)}
+            }
+            Group { title: "words",
+                {select("source", settings.source, |s, v| s.source = v)}
+                {number("batch", settings.buffer.batch, |s, v| s.buffer.batch = v)}
+                {number("lookahead", settings.buffer.lookahead, |s, v| s.buffer.lookahead = v)}
+            }
+            Group { title: "shortcuts",
+                for (action, current) in settings.shortcuts.bindings() {
+                    {shortcut(action, current.to_string())}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::race::RaceState;
+use crate::settings::update_settings;
+use crate::{AppState, MainPanel};
+use dioxus::html::input_data::keyboard_types::Code;
+use dioxus::prelude::*;
+use hemi_core::words::LayoutDictionary;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// App control that can be bound to a [`Shortcut`]
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub(crate) enum Action {
+    Restart,
+    NewTest,
+    Settings,
+    FlipSide,
+    NextLayout,
+    Sound,
+    Keyboard,
+}
+
+impl Action {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Action::Restart => "restart",
+            Action::NewTest => "new test",
+            Action::Settings => "settings",
+            Action::FlipSide => "flip side",
+            Action::NextLayout => "next layout",
+            Action::Sound => "sound",
+            Action::Keyboard => "keyboard",
+        }
+    }
+}
+
+/// Key [`Code`] pressed while another one is held, written as `Tab+Enter` or just `Escape`
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct Shortcut {
+    hold: Option<Code>,
+    press: Code,
+}
+
+impl Shortcut {
+    fn new(hold: Option<Code>, press: Code) -> Self {
+        Shortcut { hold, press }
+    }
+
+    fn matches(&self, held: &[Code], code: Code) -> bool {
+        self.press == code && self.hold.is_none_or(|hold| held.contains(&hold))
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hold {
+            Some(hold) => write!(f, "{hold}+{}", self.press),
+            None => write!(f, "{}", self.press),
+        }
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = |name: &str| {
+            Code::from_str(name.trim())
+                .ok()
+                .filter(|code| *code != Code::Unidentified)
+                .ok_or_else(|| format!("unknown key {name}"))
+        };
+
+        match s.split_once('+') {
+            Some((hold, press)) => Ok(Shortcut::new(Some(code(hold)?), code(press)?)),
+            None => Ok(Shortcut::new(None, code(s)?)),
+        }
+    }
+}
+
+impl TryFrom<String> for Shortcut {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Shortcut> for String {
+    fn from(shortcut: Shortcut) -> Self {
+        shortcut.to_string()
+    }
+}
+
+/// Shortcut bound to every [`Action`]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Shortcuts {
+    pub(crate) restart: Shortcut,
+    pub(crate) new_test: Shortcut,
+    pub(crate) settings: Shortcut,
+    pub(crate) flip_side: Shortcut,
+    pub(crate) next_layout: Shortcut,
+    pub(crate) sound: Shortcut,
+    pub(crate) keyboard: Shortcut,
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        Shortcuts {
+            restart: Shortcut::new(Some(Code::Tab), Code::Enter),
+            new_test: Shortcut::new(Some(Code::Tab), Code::KeyN),
+            settings: Shortcut::new(None, Code::Escape),
+            flip_side: Shortcut::new(Some(Code::Tab), Code::KeyF),
+            next_layout: Shortcut::new(Some(Code::Tab), Code::KeyL),
+            sound: Shortcut::new(Some(Code::Tab), Code::KeyM),
+            keyboard: Shortcut::new(Some(Code::Tab), Code::KeyK),
+        }
+    }
+}
+
+impl Shortcuts {
+    pub(crate) fn bindings(&self) -> [(Action, Shortcut); 7] {
+        [
+            (Action::Restart, self.restart),
+            (Action::NewTest, self.new_test),
+            (Action::Settings, self.settings),
+            (Action::FlipSide, self.flip_side),
+            (Action::NextLayout, self.next_layout),
+            (Action::Sound, self.sound),
+            (Action::Keyboard, self.keyboard),
+        ]
+    }
+
+    pub(crate) fn get_mut(&mut self, action: Action) -> &mut Shortcut {
+        match action {
+            Action::Restart => &mut self.restart,
+            Action::NewTest => &mut self.new_test,
+            Action::Settings => &mut self.settings,
+            Action::FlipSide => &mut self.flip_side,
+            Action::NextLayout => &mut self.next_layout,
+            Action::Sound => &mut self.sound,
+            Action::Keyboard => &mut self.keyboard,
+        }
+    }
+
+    /// Action triggered by pressing `code` while `held` keys are down
+    pub(crate) fn action(&self, held: &[Code], code: Code) -> Option<Action> {
+        // Chords take precedence over single keys bound to the same code
+        let mut bindings = self.bindings();
+        bindings.sort_by_key(|(_, shortcut)| shortcut.hold.is_none());
+
+        bindings
+            .into_iter()
+            .find(|(_, shortcut)| shortcut.matches(held, code))
+            .map(|(action, _)| action)
+    }
+
+    /// Whether `code` has to be held for some shortcut, and shouldn't act on its own
+    pub(crate) fn holds(&self, code: Code) -> bool {
+        self.bindings()
+            .iter()
+            .any(|(_, shortcut)| shortcut.hold == Some(code))
+    }
+}
+
+/// Runs `action` against the app state provided in the context
+pub(crate) fn perform(action: Action) {
+    let mut app = consume_context::<Signal<AppState>>();
+
+    match action {
+        Action::Restart => app.write().typer.restart(),
+        Action::NewTest => {
+            // Races are typed on the words given by the server, settings refusing their changes
+            if consume_context::<Signal<RaceState>>().read().is_racing() {
+                return;
+            }
+
+            let dictionary = consume_context::<Signal<LayoutDictionary>>();
+            app.write().typer.new_test(&dictionary.read());
+        }
+        Action::Settings => {
+            let panel = &mut app.write().panel;
+
+            *panel = match panel {
+                MainPanel::Settings => MainPanel::Typing,
+                _ => MainPanel::Settings,
+            };
+        }
+        Action::FlipSide => update_settings(|settings| settings.side = settings.side.flipped()),
+        Action::NextLayout => update_settings(|settings| settings.layout = settings.layout.next()),
+        Action::Sound => {
+            update_settings(|settings| settings.sound_enabled = !settings.sound_enabled)
+        }
+        Action::Keyboard => {
+            update_settings(|settings| settings.keyboard_enabled = !settings.keyboard_enabled)
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use dioxus::html::input_data::keyboard_types::Code;
+use futures_util::future::join_all;
+use gloo_net::http::Request;
+use hemi_core::words::TypingEvent;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{AudioBuffer, AudioContext, AudioContextState, GainNode};
+
+/// Sound packs shipped in `assets`, each described by its `pack.json` manifest
+pub(crate) const BUNDLED_PACKS: [&str; 1] = ["tealios"];
+
+/// Group of keys sharing their sounds, unless the manifest maps the [`Code`] itself
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum KeyClass {
+    Letter,
+    Digit,
+    Punctuation,
+    Space,
+    Enter,
+    Backspace,
+    Modifier,
+    Other,
+}
+
+impl KeyClass {
+    fn of(code: &Code) -> Self {
+        match code {
+            Code::Space => KeyClass::Space,
+            Code::Enter | Code::NumpadEnter => KeyClass::Enter,
+            Code::Backspace | Code::Delete => KeyClass::Backspace,
+            Code::ShiftLeft
+            | Code::ShiftRight
+            | Code::ControlLeft
+            | Code::ControlRight
+            | Code::AltLeft
+            | Code::AltRight
+            | Code::MetaLeft
+            | Code::MetaRight
+            | Code::CapsLock
+            | Code::Tab => KeyClass::Modifier,
+            Code::Backquote
+            | Code::Minus
+            | Code::Equal
+            | Code::BracketLeft
+            | Code::BracketRight
+            | Code::Backslash
+            | Code::Semicolon
+            | Code::Quote
+            | Code::Comma
+            | Code::Period
+            | Code::Slash => KeyClass::Punctuation,
+            code => {
+                let name = code.to_string();
+                if name.starts_with("Key") {
+                    KeyClass::Letter
+                } else if name.starts_with("Digit") {
+                    KeyClass::Digit
+                } else {
+                    KeyClass::Other
+                }
+            }
+        }
+    }
+}
+
+/// Optional feedback sound on a [`TypingEvent`], besides the sounds of keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Cue {
+    Error,
+    Streak,
+    Finish,
+}
+
+impl Cue {
+    /// Frequency and length of the tone played when the sound pack has no samples for the cue
+    fn tone(&self) -> (f32, f64) {
+        match self {
+            Cue::Error => (220.0, 0.15),
+            Cue::Streak => (880.0, 0.2),
+            Cue::Finish => (660.0, 0.5),
+        }
+    }
+}
+
+/// How often the streak cue plays
+#[derive(Clone, Debug, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum StreakCue {
+    Off,
+    Every(i32),
+}
+
+/// Cues enabled in the settings
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Cues {
+    pub(crate) error: bool,
+    pub(crate) streak: StreakCue,
+    pub(crate) finish: bool,
+}
+
+impl Default for Cues {
+    fn default() -> Self {
+        Cues {
+            error: false,
+            streak: StreakCue::Every(25),
+            finish: true,
+        }
+    }
+}
+
+impl Cues {
+    /// Cue to play on `event`, if it's enabled
+    pub(crate) fn on(&self, event: TypingEvent) -> Option<Cue> {
+        match (event, self.streak) {
+            (TypingEvent::Mistake, _) if self.error => Some(Cue::Error),
+            // Stored settings are edited by hand, and may ask for a cue every 0 words
+            (TypingEvent::Streak(streak), StreakCue::Every(every))
+                if every > 0 && streak % every == 0 =>
+            {
+                Some(Cue::Streak)
+            }
+            (TypingEvent::Finished, _) if self.finish => Some(Cue::Finish),
+            _ => None,
+        }
+    }
+}
+
+/// Manifest of a sound pack, listing sample files relative to the manifest itself.
+///
+/// Each key plays one of the variants mapped to its [`Code`], then to its [`KeyClass`],
+/// then the fallback ones, picked at random.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct SoundPack {
+    /// Variants for specific key codes, such as `KeyA` or `ShiftLeft`
+    keys: HashMap<String, Vec<String>>,
+    classes: HashMap<KeyClass, Vec<String>>,
+    fallback: Vec<String>,
+    cues: HashMap<Cue, Vec<String>>,
+}
+
+/// Samples of the selected sound pack, decoded up front and played through one Web Audio context
+pub(crate) struct AudioLibrary {
+    context: AudioContext,
+    /// Volume of every sample, shared by all of them
+    gain: GainNode,
+    samples: HashMap<String, AudioBuffer>,
+    keys: HashMap<Code, Vec<String>>,
+    classes: HashMap<KeyClass, Vec<String>>,
+    fallback: Vec<String>,
+    cues: HashMap<Cue, Vec<String>>,
+}
+
+impl AudioLibrary {
+    /// Resolves files of the `pack` against the `base` path of its manifest,
+    /// if audio is available in the browser
+    fn new(pack: SoundPack, base: &str) -> Option<Self> {
+        let resolve = |files: Vec<String>| -> Vec<String> {
+            files
+                .into_iter()
+                .map(|file| format!("{base}{file}"))
+                .collect()
+        };
+
+        let keys = pack
+            .keys
+            .into_iter()
+            .filter_map(|(key, files)| match Code::from_str(&key) {
+                Ok(code) => Some((code, resolve(files))),
+                Err(_) => {
+                    log::warn!("unknown key {key} in sound pack");
+                    None
+                }
+            })
+            .collect();
+
+        let context = AudioContext::new().ok()?;
+        let gain = context.create_gain().ok()?;
+        gain.connect_with_audio_node(&context.destination()).ok()?;
+
+        Some(AudioLibrary {
+            context,
+            gain,
+            samples: HashMap::new(),
+            keys,
+            classes: pack
+                .classes
+                .into_iter()
+                .map(|(class, files)| (class, resolve(files)))
+                .collect(),
+            fallback: resolve(pack.fallback),
+            cues: pack
+                .cues
+                .into_iter()
+                .map(|(cue, files)| (cue, resolve(files)))
+                .collect(),
+        })
+    }
+
+    /// Loads and decodes the sound pack called `pack` from the bundled ones, or from its manifest URL
+    pub(crate) async fn load(pack: &str) -> Option<Self> {
+        let manifest = if pack.contains('/') {
+            pack.to_owned()
+        } else {
+            format!("assets/{pack}/pack.json")
+        };
+        let base = manifest.rsplit_once('/').map_or("", |(base, _)| base);
+
+        let response = Request::get(&manifest).send().await;
+        let pack = match response {
+            Ok(response) => response.json::<SoundPack>().await,
+            Err(err) => Err(err),
+        };
+
+        let pack = match pack {
+            Ok(pack) => pack,
+            Err(err) => {
+                log::warn!("failed to load sound pack {manifest}: {err}");
+                return None;
+            }
+        };
+
+        let Some(mut library) = AudioLibrary::new(pack, &format!("{base}/")) else {
+            log::warn!("audio is unavailable");
+            return None;
+        };
+
+        let paths: HashSet<String> = library
+            .keys
+            .values()
+            .chain(library.classes.values())
+            .chain(library.cues.values())
+            .chain([&library.fallback])
+            .flatten()
+            .cloned()
+            .collect();
+        let samples = join_all(paths.into_iter().map(|path| {
+            let library = &library;
+            async move {
+                let sample = library.decode(&path).await;
+                (path, sample)
+            }
+        }))
+        .await;
+
+        for (path, sample) in samples {
+            match sample {
+                Some(sample) => {
+                    library.samples.insert(path, sample);
+                }
+                None => log::warn!("failed to decode {path}"),
+            }
+        }
+
+        Some(library)
+    }
+
+    async fn decode(&self, path: &str) -> Option<AudioBuffer> {
+        let bytes = Request::get(path).send().await.ok()?.binary().await.ok()?;
+        let data = js_sys::Uint8Array::from(bytes.as_slice()).buffer();
+        let decoding = self.context.decode_audio_data(&data).ok()?;
+
+        JsFuture::from(decoding).await.ok()?.dyn_into().ok()
+    }
+
+    fn variants(&self, key: &Code) -> &[String] {
+        self.keys
+            .get(key)
+            .or_else(|| self.classes.get(&KeyClass::of(key)))
+            .unwrap_or(&self.fallback)
+    }
+
+    /// Sets the volume of all samples, from 0 to 1
+    pub(crate) fn set_volume(&self, volume: f32) {
+        self.gain.gain().set_value(volume);
+    }
+
+    /// Plays a sample of `key`, overlapping with the ones still playing
+    pub(crate) fn play(&self, key: Code) {
+        if let Some(sample) = self.sample(self.variants(&key)) {
+            self.play_sample(sample);
+        }
+    }
+
+    /// Plays a sample of `cue`, or a tone if the sound pack has none
+    pub(crate) fn play_cue(&self, cue: Cue) {
+        let variants = self.cues.get(&cue).map_or(&[][..], Vec::as_slice);
+        match self.sample(variants) {
+            Some(sample) => self.play_sample(sample),
+            None => self.play_tone(cue.tone()),
+        }
+    }
+
+    fn sample(&self, variants: &[String]) -> Option<&AudioBuffer> {
+        variants
+            .choose(&mut rand::thread_rng())
+            .and_then(|path| self.samples.get(path))
+    }
+
+    /// Browsers keep audio suspended until the user interacts with the page
+    fn resume(&self) {
+        if self.context.state() == AudioContextState::Suspended {
+            let _ = self.context.resume();
+        }
+    }
+
+    fn play_sample(&self, sample: &AudioBuffer) {
+        self.resume();
+
+        let played = self.context.create_buffer_source().and_then(|source| {
+            source.set_buffer(Some(sample));
+            source.connect_with_audio_node(&self.gain)?;
+            source.start()
+        });
+
+        if played.is_err() {
+            log::warn!("failed to play sample");
+        }
+    }
+
+    /// Plays a sine tone of `frequency` fading out over `seconds`
+    fn play_tone(&self, (frequency, seconds): (f32, f64)) {
+        self.resume();
+
+        let now = self.context.current_time();
+        let played = self.context.create_oscillator().and_then(|oscillator| {
+            let fade = self.context.create_gain()?;
+            fade.gain()
+                .linear_ramp_to_value_at_time(0.0, now + seconds)?;
+            oscillator.frequency().set_value(frequency);
+            oscillator.connect_with_audio_node(&fade)?;
+            fade.connect_with_audio_node(&self.gain)?;
+            oscillator.start()?;
+            oscillator.stop_with_when(now + seconds)
+        });
+
+        if played.is_err() {
+            log::warn!("failed to play tone");
+        }
+    }
+}
+
+impl Drop for AudioLibrary {
+    fn drop(&mut self) {
+        let _ = self.context.close();
+    }
+}
@@ -0,0 +1,52 @@
+use hemi_core::words::TypingSide;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Reads JSON value stored under `key` in the browser's local storage
+pub(crate) fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let value = local_storage()?.get_item(key).ok()??;
+    serde_json::from_str(&value).ok()
+}
+
+/// Stores `value` as JSON under `key` in the browser's local storage
+pub(crate) fn save<T: Serialize>(key: &str, value: &T) {
+    let (Some(storage), Ok(value)) = (local_storage(), serde_json::to_string(value)) else {
+        log::warn!("failed to save {key}");
+        return;
+    };
+
+    let _ = storage.set_item(key, &value);
+}
+
+/// Best words per minute recorded for each layout and hand
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub(crate) struct PersonalBests {
+    wpm: HashMap<String, f64>,
+}
+
+impl PersonalBests {
+    const KEY: &'static str = "hemi.bests";
+
+    pub(crate) fn load() -> Self {
+        load(Self::KEY).unwrap_or_default()
+    }
+
+    pub(crate) fn get(&self, layout: &str, side: TypingSide) -> Option<f64> {
+        self.wpm.get(&format!("{layout}.{}", side.name())).copied()
+    }
+
+    /// Records `wpm` if it beats the best for `layout` and `side`, saving the bests if so
+    pub(crate) fn record(&mut self, layout: &str, side: TypingSide, wpm: f64) -> bool {
+        if self.get(layout, side).is_some_and(|best| best >= wpm) {
+            return false;
+        }
+
+        self.wpm.insert(format!("{layout}.{}", side.name()), wpm);
+        save(Self::KEY, self);
+        true
+    }
+}
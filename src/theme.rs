@@ -0,0 +1,183 @@
+use crate::settings::{update_settings, SELECT_STYLE};
+use crate::AppState;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Color scheme of the app, applied as CSS variables used by the `theme-*` Tailwind colors
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Theme {
+    pub(crate) name: String,
+    /// Page background
+    pub(crate) background: String,
+    /// Keys, highlighted text and other raised elements
+    pub(crate) surface: String,
+    pub(crate) text: String,
+    /// Secondary text, such as the status bar and disabled keys
+    pub(crate) muted: String,
+    /// End color of the gradient on the previous and next words
+    pub(crate) accent: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin()[0].clone()
+    }
+}
+
+impl Theme {
+    fn new(name: &str, colors: [&str; 5]) -> Self {
+        let [background, surface, text, muted, accent] = colors.map(str::to_owned);
+
+        Theme {
+            name: name.to_owned(),
+            background,
+            surface,
+            text,
+            muted,
+            accent,
+        }
+    }
+
+    /// Themes shipped with the app, the first one being the default
+    pub(crate) fn builtin() -> [Theme; 4] {
+        [
+            Theme::new(
+                "dark",
+                ["#000000", "#27272a", "#ffffff", "#a1a1aa", "#e4e4e7"],
+            ),
+            Theme::new(
+                "light",
+                ["#fafafa", "#e4e4e7", "#18181b", "#52525b", "#3f3f46"],
+            ),
+            Theme::new(
+                "high contrast",
+                ["#000000", "#1f1f1f", "#ffffff", "#ffff00", "#ffffff"],
+            ),
+            Theme::new(
+                "solarized",
+                ["#002b36", "#073642", "#fdf6e3", "#93a1a1", "#eee8d5"],
+            ),
+        ]
+    }
+
+    /// Colors of the theme along with the name of their CSS variable
+    fn colors(&self) -> [(&'static str, &str); 5] {
+        [
+            ("background", &self.background),
+            ("surface", &self.surface),
+            ("text", &self.text),
+            ("muted", &self.muted),
+            ("accent", &self.accent),
+        ]
+    }
+
+    /// Checks every value of the theme is a CSS color, as they end up in an inline style
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let invalid = self
+            .colors()
+            .into_iter()
+            .find(|(_, value)| !is_color(value));
+
+        match invalid {
+            Some((name, value)) => Err(format!("{name} is not a color: {value}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Inline style declaring the CSS variables of the theme
+    pub(crate) fn style(&self) -> String {
+        self.colors()
+            .map(|(name, value)| format!("--theme-{name}: {value};"))
+            .join(" ")
+    }
+}
+
+/// Whether `value` is a hex color like `#fafafa`, a color function like `rgb(0 0 0 / 50%)`
+/// or a named color like `teal`
+fn is_color(value: &str) -> bool {
+    let value = value.trim();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    if let Some((function, arguments)) = value.split_once('(') {
+        let Some(arguments) = arguments.strip_suffix(')') else {
+            return false;
+        };
+
+        return matches!(
+            function,
+            "rgb" | "rgba" | "hsl" | "hsla" | "hwb" | "lab" | "lch" | "oklab" | "oklch"
+        ) && arguments
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " .,%/+-".contains(c));
+    }
+
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Picks one of the built-in themes, or imports and exports the current one as JSON
+pub(crate) fn ThemePicker() -> Element {
+    let app = use_context::<Signal<AppState>>();
+    let theme = app.read().settings.theme.clone();
+    let exported = serde_json::to_string_pretty(&theme).expect("Themes are serializable");
+    let mut json = use_signal(|| exported.clone());
+    let mut error = use_signal(|| None::<String>);
+
+    let builtin = Theme::builtin();
+    let custom = !builtin.contains(&theme);
+
+    let choose = move |e: Event<FormData>| {
+        if let Some(theme) = Theme::builtin().into_iter().find(|t| t.name == e.value()) {
+            json.set(serde_json::to_string_pretty(&theme).expect("Themes are serializable"));
+            update_settings(|settings| settings.theme = theme);
+        }
+    };
+
+    let import = move |_| {
+        let theme = serde_json::from_str::<Theme>(&json.read()).map_err(|err| err.to_string());
+        match theme.and_then(|theme| theme.validate().map(|_| theme)) {
+            Ok(theme) => {
+                error.set(None);
+                update_settings(|settings| settings.theme = theme);
+            }
+            Err(err) => error.set(Some(format!("invalid theme: {err}"))),
+        }
+    };
+
+    rsx! {
+        label { class: "flex flex-row justify-between items-center gap-5",
+            span { "theme" }
+            select { class: SELECT_STYLE,
+                onchange: choose,
+                for choice in builtin {
+                    option { value: "{choice.name}", selected: choice == theme, "{choice.name}" }
+                }
+                if custom {
+                    option { value: "", selected: true, "{theme.name}" }
+                }
+            }
+        }
+        textarea { class: "bg-transparent border border-theme-muted text-xs rounded-lg p-1 h-32",
+            aria_label: "theme as JSON",
+            value: "{json}",
+            onkeydown: |e| e.stop_propagation(),
+            onkeypress: |e| e.stop_propagation(),
+            oninput: move |e| json.set(e.value()),
+        }
+        div { class: "flex flex-row gap-3",
+            button { class: "underline", onclick: import, "import" }
+            button { class: "underline",
+                onclick: move |_| {
+                    error.set(None);
+                    json.set(exported.clone());
+                },
+                "export"
+            }
+        }
+        if let Some(error) = error() {
+            p { class: "text-theme-muted", "{error}" }
+        }
+    }
+}
@@ -5,7 +5,17 @@ module.exports = {
     "./dist/**/*.html",
   ],
   theme: {
-    extend: {},
+    extend: {
+      colors: {
+        theme: {
+          background: "var(--theme-background)",
+          surface: "var(--theme-surface)",
+          text: "var(--theme-text)",
+          muted: "var(--theme-muted)",
+          accent: "var(--theme-accent)",
+        },
+      },
+    },
   },
   plugins: [],
 }
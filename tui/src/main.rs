@@ -0,0 +1,236 @@
+//! Terminal front end of Hemi, working offline with the bundled dictionaries.
+//!
+//! Usage: `hemi-tui [qwerty|colemak|dvorak|workman|jcuken|<layout file>] [left|right]`,
+//! where a layout file is an XKB symbols or KLC file.
+
+use hemi_core::geometry::Geometry;
+use hemi_core::import;
+use hemi_core::keys::{KeyMap, KeyboardState};
+use hemi_core::words::{LayoutDictionary, Layouts, TypingData, TypingSide};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+struct App {
+    layout: String,
+    dictionary: LayoutDictionary,
+    keymap: KeyMap,
+    keyboard: KeyboardState,
+    typer: TypingData,
+    side: TypingSide,
+    pressed: Option<char>,
+}
+
+impl App {
+    fn new(layout: String, dictionary: LayoutDictionary, side: TypingSide) -> Self {
+        let keymap = KeyMap::new(&dictionary);
+
+        App {
+            keyboard: KeyboardState::new(&keymap, side, Geometry::Ansi),
+            typer: TypingData::new(Default::default(), &dictionary, side, Default::default()),
+            layout,
+            dictionary,
+            keymap,
+            side,
+            pressed: None,
+        }
+    }
+
+    fn flip_side(&mut self) {
+        self.side = self.side.flipped();
+        self.typer.set_side(self.side);
+        self.typer.reset_session();
+        self.typer.refill(&self.dictionary);
+        self.keyboard = KeyboardState::new(&self.keymap, self.side, Geometry::Ansi);
+    }
+
+    /// Highlights the key of the last typed character, as terminals don't report key releases
+    fn press(&mut self, char: Option<char>) {
+        for (char, enabled) in [(self.pressed, false), (char, true)] {
+            if let Some(code) = char.and_then(|char| self.keymap.code_of(char)) {
+                self.keyboard.update_for(&code, enabled);
+            }
+        }
+
+        self.pressed = char;
+    }
+
+    /// Handles a key press, returns `false` once the user quits
+    fn on_key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => return false,
+            KeyCode::Char('c') if control => return false,
+            KeyCode::Tab => self.flip_side(),
+            KeyCode::Char('r') if control => self.typer.restart(),
+            KeyCode::Char('n') if control => self.typer.new_test(&self.dictionary),
+            // Other control chords don't type anything
+            KeyCode::Char(_) if control => (),
+            KeyCode::Backspace => {
+                self.typer.pop();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                self.typer.submit();
+                self.press(None);
+            }
+            KeyCode::Char(char) => {
+                self.typer.push_str(&char.to_string());
+                self.press(Some(char));
+            }
+            _ => (),
+        }
+
+        self.typer.refill(&self.dictionary);
+
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, status, words, keyboard, help] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .flex(Flex::Center)
+        .areas(frame.area());
+
+        let title = Line::from(vec![
+            Span::from(" Hemi ").reversed().bold(),
+            Span::from(" Typer").bold(),
+            Span::from(format!("  {} / {}", self.layout, self.side.name())).dark_gray(),
+        ]);
+        frame.render_widget(Paragraph::new(title).centered(), header);
+
+        let status_line = match self.typer.result() {
+            Some(result) => format!(
+                "{:.0} wpm  {:.0}%  press space to continue",
+                result.wpm(),
+                result.accuracy()
+            ),
+            None => format!(
+                "streak: {}  wpm: {:.0}  accuracy: {:.0}%",
+                self.typer.streak(),
+                self.typer.wpm(),
+                self.typer.accuracy()
+            ),
+        };
+        frame.render_widget(Paragraph::new(status_line).centered().dark_gray(), status);
+
+        let [prev, current, next] = Layout::horizontal([Constraint::Length(20); 3])
+            .flex(Flex::Center)
+            .spacing(2)
+            .areas(words);
+        let word_style = Style::default().add_modifier(Modifier::BOLD);
+        frame.render_widget(
+            Paragraph::new(self.typer.last_word())
+                .right_aligned()
+                .style(word_style.fg(Color::Gray)),
+            prev,
+        );
+        frame.render_widget(
+            Paragraph::new(self.typer.input()).style(word_style.fg(Color::White)),
+            current,
+        );
+        frame.render_widget(
+            Paragraph::new(self.typer.next_word().unwrap_or(" ")).style(word_style.fg(Color::Gray)),
+            next,
+        );
+
+        let rows: Vec<Line> = self
+            .keyboard
+            .keys()
+            .iter()
+            .map(|row| {
+                let keys = row.iter().map(|key| {
+                    let legend = format!("[{}]", key.legend());
+                    if key.enabled() {
+                        Span::from(legend).reversed()
+                    } else if key.active() {
+                        Span::from(legend)
+                    } else {
+                        Span::from(legend).dark_gray()
+                    }
+                });
+                Line::from_iter(keys)
+            })
+            .collect();
+        let width = rows.iter().map(Line::width).max().unwrap_or_default() as u16;
+        let [keyboard] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(keyboard);
+        frame.render_widget(Paragraph::new(rows), keyboard);
+
+        frame.render_widget(
+            Paragraph::new("esc quit  tab flip side  ctrl+r restart  ctrl+n new test")
+                .centered()
+                .dark_gray(),
+            help,
+        );
+    }
+}
+
+fn run(mut terminal: DefaultTerminal, mut app: App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        // Polls with a timeout, so the status keeps updating between keystrokes
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !app.on_key(key) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Name and dictionary of the bundled layout called `layout`, or of the layout file at that path
+fn load_layout(layout: String) -> io::Result<(String, LayoutDictionary)> {
+    let layouts = Layouts::bundled();
+    if let Some(dictionary) = layouts.get(&layout) {
+        return Ok((layout, dictionary.clone()));
+    }
+
+    let path = Path::new(&layout);
+    if !path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("unknown layout {layout}, neither a bundled layout nor a file"),
+        ));
+    }
+
+    let text = import::decode(&std::fs::read(path)?);
+    let keys =
+        import::import(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let name = path
+        .file_stem()
+        .map_or(layout.clone(), |name| name.to_string_lossy().into_owned());
+
+    Ok((name, keys.dictionary(layouts.words())))
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (layout, dictionary) = load_layout(args.next().unwrap_or_else(|| "qwerty".to_owned()))?;
+    let side = match args.next().as_deref() {
+        Some("right") => TypingSide::Right,
+        _ => TypingSide::Left,
+    };
+
+    let app = App::new(layout, dictionary, side);
+    let terminal = ratatui::init();
+    let result = run(terminal, app);
+    ratatui::restore();
+    result
+}